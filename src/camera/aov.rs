@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use crate::{
+    hittable::{HitRecord, Hittable, hittable_list::HittableList, material::Material},
+    ray::Ray,
+    sampler::Sampler,
+    utility::{hash, interval::Interval},
+    vec3::{Color, Vec3},
+};
+
+use super::Camera;
+
+// Auxiliary pass rendered next to the image, from where camera rays first hit the scene. They
+// are filtered with the same samples and weights as the image, so edges line up with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aov {
+    Depth,      // Distance along the view direction, the same in every channel
+    Normal,     // World space shading normal, facing the camera, components in [-1, 1]
+    Albedo,     // Base color of the surface without any lighting
+    Position,   // World space hit point
+    MaterialId, // Flat random color per material
+    ObjectId,   // Flat random color per object of the scene
+    Coverage,   // Share of the camera rays that hit anything, the same in every channel
+}
+
+impl Aov {
+    pub const NAMES: [&str; 7] = [
+        "depth",
+        "normal",
+        "albedo",
+        "position",
+        "material-id",
+        "object-id",
+        "coverage",
+    ];
+    pub const ALL: [Aov; 7] = [
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::Position,
+        Aov::MaterialId,
+        Aov::ObjectId,
+        Aov::Coverage,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        let index = Self::NAMES.iter().position(|&n| n == name)?;
+        Some(Self::ALL[index])
+    }
+
+    pub fn name(&self) -> &'static str {
+        Self::NAMES[self.index()]
+    }
+
+    pub fn index(&self) -> usize {
+        Self::ALL.iter().position(|aov| aov == self).unwrap()
+    }
+}
+
+impl Camera {
+    // Numbers the materials of the world in the order a walk over it first meets them. The
+    // walk only depends on the scene, so a scene always gets the same ids, also on resume
+    pub(super) fn number_materials(&mut self, world: &HittableList) {
+        self.material_ids.clear();
+        if !self.aovs.contains(&Aov::MaterialId) {
+            return;
+        }
+
+        world.materials(&mut |material| {
+            let next = self.material_ids.len() as u32;
+            self.material_ids
+                .entry(material_key(material))
+                .or_insert(next);
+        });
+    }
+
+    // Values of the requested passes for a camera ray, all zero where it escapes. Runs after
+    // the path of the sample is done, so the random numbers a volume uses to place the hit do
+    // not change the image.
+    pub(super) fn aov_values(
+        &self,
+        r: &Ray,
+        world: &HittableList,
+        sampler: &mut dyn Sampler,
+    ) -> Vec<Color> {
+        if self.aovs.is_empty() {
+            return vec![];
+        }
+
+        let mut rec = HitRecord::default();
+        if !world.hit(r, Interval::new(0.001, f64::INFINITY), &mut rec, sampler) {
+            return vec![Color::default(); self.aovs.len()];
+        }
+
+        let material = rec.mat.as_ref().unwrap();
+        self.aovs
+            .iter()
+            .map(|aov| match aov {
+                Aov::Depth => {
+                    let depth = rec.t * Vec3::dot(r.direction(), &-self.w);
+                    Color::new(depth, depth, depth)
+                }
+                Aov::Normal => rec.normal,
+                Aov::Albedo => material.albedo(&rec),
+                Aov::Position => rec.p,
+                Aov::MaterialId => id_color(
+                    self.material_ids
+                        .get(&material_key(material))
+                        .copied()
+                        .unwrap_or(0),
+                ),
+                Aov::ObjectId => id_color(rec.object_id),
+                Aov::Coverage => Color::new(1.0, 1.0, 1.0),
+            })
+            .collect()
+    }
+}
+
+// Materials are told apart by where they live, shared ones are the same material
+fn material_key(material: &Arc<dyn Material>) -> usize {
+    Arc::as_ptr(material) as *const () as usize
+}
+
+// Bright random color picked by an id, neighbouring ids get unrelated colors
+fn id_color(id: u32) -> Color {
+    let bits = hash(&[id as u64]);
+    let channel = |shift: u32| 0.2 + 0.8 * ((bits >> shift) & 0xFF) as f64 / 255.0;
+    Color::new(channel(0), channel(8), channel(16))
+}
@@ -0,0 +1,129 @@
+use std::f64::consts::PI;
+
+// Shape of the reconstruction filter, which spreads every sample over the pixels around it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FilterKind {
+    // Every sample counts fully towards the pixel it landed in, and no others
+    #[default]
+    Box,
+    Tent,     // Falls off linearly towards the radius
+    Gaussian, // Smooth and slightly soft
+    Mitchell, // Mitchell-Netravali with B = C = 1/3, sharper than the Gaussian
+    Lanczos,  // Windowed sinc, the sharpest, can ring around hard edges
+}
+
+impl FilterKind {
+    pub const NAMES: [&str; 5] = ["box", "tent", "gaussian", "mitchell", "lanczos"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "box" => Some(FilterKind::Box),
+            "tent" => Some(FilterKind::Tent),
+            "gaussian" => Some(FilterKind::Gaussian),
+            "mitchell" => Some(FilterKind::Mitchell),
+            "lanczos" => Some(FilterKind::Lanczos),
+            _ => None,
+        }
+    }
+
+    // Radius in pixels used unless one is given
+    pub fn default_radius(&self) -> f64 {
+        match self {
+            FilterKind::Box => 0.5,
+            FilterKind::Tent => 1.0,
+            FilterKind::Gaussian => 1.5,
+            FilterKind::Mitchell => 2.0,
+            FilterKind::Lanczos => 2.0,
+        }
+    }
+}
+
+// Separable pixel reconstruction filter, the radius is in pixels and goes out from the sample
+// along each axis
+#[derive(Clone, Copy, Debug)]
+pub struct Filter {
+    pub kind: FilterKind,
+    pub radius: f64,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::new(FilterKind::default())
+    }
+}
+
+impl Filter {
+    pub fn new(kind: FilterKind) -> Self {
+        Self {
+            kind,
+            radius: kind.default_radius(),
+        }
+    }
+
+    pub fn with_radius(mut self, radius: f64) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    // How many pixels a sample can reach past the one it was taken in, along each axis
+    pub(super) fn reach(&self) -> u32 {
+        (self.radius + 0.5).ceil() as u32 - 1
+    }
+
+    // Weight of a sample for a pixel whose center is (x, y) pixels away from it. Mitchell and
+    // Lanczos go negative in places, the weights are normalized by their sum per pixel.
+    pub(super) fn evaluate(&self, x: f64, y: f64) -> f64 {
+        self.evaluate_1d(x) * self.evaluate_1d(y)
+    }
+
+    fn evaluate_1d(&self, x: f64) -> f64 {
+        let radius = self.radius;
+
+        // Half open, so with the default radius a sample on the border between two pixels
+        // goes to exactly one of them
+        if x < -radius || x >= radius {
+            return 0.0;
+        }
+
+        match self.kind {
+            FilterKind::Box => 1.0,
+            FilterKind::Tent => radius - x.abs(),
+            FilterKind::Gaussian => {
+                // Shifted down so it reaches zero at the radius instead of cutting off
+                const ALPHA: f64 = 2.0;
+                f64::max(
+                    f64::exp(-ALPHA * x * x) - f64::exp(-ALPHA * radius * radius),
+                    0.0,
+                )
+            }
+            FilterKind::Mitchell => {
+                const B: f64 = 1.0 / 3.0;
+                const C: f64 = 1.0 / 3.0;
+
+                // The cubic is defined over [-2, 2], stretch it to the radius
+                let t = (2.0 * x / radius).abs();
+                if t < 1.0 {
+                    ((12.0 - 9.0 * B - 6.0 * C) * t * t * t
+                        + (-18.0 + 12.0 * B + 6.0 * C) * t * t
+                        + (6.0 - 2.0 * B))
+                        / 6.0
+                } else {
+                    ((-B - 6.0 * C) * t * t * t
+                        + (6.0 * B + 30.0 * C) * t * t
+                        + (-12.0 * B - 48.0 * C) * t
+                        + (8.0 * B + 24.0 * C))
+                        / 6.0
+                }
+            }
+            // The sinc window is as wide as the filter
+            FilterKind::Lanczos => sinc(x) * sinc(x / radius),
+        }
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        return 1.0;
+    }
+    f64::sin(PI * x) / (PI * x)
+}
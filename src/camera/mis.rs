@@ -0,0 +1,112 @@
+use crate::{
+    hittable::{HitRecord, Hittable, hittable_list::HittableList, material::ScatterRecord},
+    pdf::power_heuristic,
+    ray::Ray,
+    sampler::Sampler,
+    utility::interval::Interval,
+    vec3::Color,
+};
+
+use super::Camera;
+
+impl Camera {
+    // Path tracer with next event estimation. Every non-specular bounce takes one sample
+    // towards the lights and one from the material, the material sample also carries the path
+    // on. Emitters found by either one are weighted with the power heuristic, so light that
+    // both strategies can reach is not counted twice.
+    pub(super) fn ray_color_mis(
+        &self,
+        r: &Ray,
+        world: &HittableList,
+        lights: &HittableList,
+        sampler: &mut dyn Sampler,
+    ) -> Color {
+        let mut color = Color::default();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut r = *r;
+
+        // Material density `r` was drawn with, None for camera rays and rays leaving specular
+        // surfaces, which light sampling can not reproduce
+        let mut bsdf_pdf: Option<f64> = None;
+
+        for bounce in 0..self.max_depth {
+            let mut rec = HitRecord::default();
+            if !world.hit(&r, Interval::new(0.001, f64::INFINITY), &mut rec, sampler) {
+                color += throughput * self.background.color(&r);
+                break;
+            }
+
+            let material = rec.mat.as_ref().unwrap();
+
+            let mut color_from_emission = material.emitted(&r, &rec);
+            if let Some(bsdf_pdf) = bsdf_pdf
+                && color_from_emission.length_squared() > 0.0
+            {
+                let light_pdf = lights.pdf_value(r.origin(), r.direction(), r.time());
+                color_from_emission = power_heuristic(bsdf_pdf, light_pdf) * color_from_emission;
+            }
+            color += throughput * color_from_emission;
+
+            let mut srec = ScatterRecord::default();
+            if !self.survives_roulette(bounce, &mut throughput, sampler)
+                || !material.scatter(&r, &rec, sampler.get_1d(), &mut srec)
+            {
+                break;
+            }
+
+            // Delta lobes can not be hit by light samples, follow them with full weight
+            let Some(material_pdf) = srec.pdf.as_deref() else {
+                throughput *= srec.attenuation;
+                r = srec.skip_pdf_ray;
+                bsdf_pdf = None;
+                continue;
+            };
+
+            // Light sample, only the emission of whatever the shadow ray reaches first counts
+            if !lights.objects.is_empty() {
+                let shadow_ray = Ray::with_time(
+                    rec.p,
+                    lights.random(&rec.p, r.time(), sampler.get_2d()),
+                    r.time(),
+                );
+                let light_pdf = lights.pdf_value(&rec.p, shadow_ray.direction(), r.time());
+                let scattering_pdf = material.scattering_pdf(&r, &rec, &shadow_ray);
+
+                if light_pdf > 0.0 && scattering_pdf > 0.0 {
+                    let mut light_rec = HitRecord::default();
+                    let radiance = if world.hit(
+                        &shadow_ray,
+                        Interval::new(0.001, f64::INFINITY),
+                        &mut light_rec,
+                        sampler,
+                    ) {
+                        let light_material = light_rec.mat.as_ref().unwrap();
+                        light_material.emitted(&shadow_ray, &light_rec)
+                    } else {
+                        self.background.color(&shadow_ray)
+                    };
+
+                    let weight =
+                        power_heuristic(light_pdf, material_pdf.value(shadow_ray.direction()));
+                    color += throughput
+                        * ((weight * scattering_pdf / light_pdf) * srec.attenuation * radiance);
+                }
+            }
+
+            // Material sample, continues the path and picks up the rest of the emission
+            let scattered =
+                Ray::with_time(rec.p, material_pdf.generate(sampler.get_2d()), r.time());
+            let pdf_value = material_pdf.value(scattered.direction());
+            if pdf_value <= 0.0 {
+                break;
+            }
+
+            let scattering_pdf = material.scattering_pdf(&r, &rec, &scattered);
+            throughput *= srec.attenuation * scattering_pdf / pdf_value;
+            r = scattered;
+            bsdf_pdf = Some(pdf_value);
+        }
+
+        color
+    }
+}
@@ -1,29 +1,113 @@
-use std::f64::INFINITY;
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+    thread,
+};
+
+pub mod aov;
+pub mod filter;
+mod mis;
+pub mod progressive;
+
+use anyhow::{Result, bail};
+use aov::Aov;
+use filter::Filter;
+use progressive::{PixelState, RenderSettings, RenderState, StripMerge};
 
 use crate::{
-    hittable::{HitRecord, Hittable, hittable_list::HittableList},
-    image::{Image, ToFile, ppm::PPM},
+    hittable::{HitRecord, Hittable, hittable_list::HittableList, material::ScatterRecord},
+    image::Image,
+    pdf::{Pdf, hittable::HittablePdf, mixture::MixturePdf},
     ray::Ray,
+    sampler::{Sampler, SamplerKind},
     timer::Timer,
-    utility::{degrees_to_radians, interval::Interval, random_double},
+    utility::{degrees_to_radians, hash, interval::Interval, seed_rng_stream},
     vec3::{Color, Point3, Vec3},
 };
 
+// What a ray sees when it leaves the scene without hitting anything
+#[derive(Clone, Copy, Debug)]
+pub enum Background {
+    Sky,          // Gradient from white at the horizon to light blue overhead
+    Solid(Color), // Constant color, black makes the scene lit only by its own emitters
+}
+
+impl Background {
+    pub fn color(&self, r: &Ray) -> Color {
+        match self {
+            Background::Sky => {
+                let unit_direction = Vec3::unit_vector(r.direction());
+                let a = 0.5 * (unit_direction.y() + 1.0);
+                (1.0 - a) * Vec3::new(1.0, 1.0, 1.0) + a * Vec3::new(0.5, 0.7, 1.0)
+            }
+            Background::Solid(color) => *color,
+        }
+    }
+}
+
+// How the radiance along a camera ray is estimated
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Integrator {
+    // One scattered ray per bounce, drawn from an even mix of light and material sampling
+    #[default]
+    Naive,
+    // Next event estimation at every non-specular bounce, light and material samples are
+    // combined with multiple importance sampling
+    Mis,
+}
+
+impl Integrator {
+    pub const NAMES: [&str; 2] = ["naive", "mis"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "naive" => Some(Integrator::Naive),
+            "mis" => Some(Integrator::Mis),
+            _ => None,
+        }
+    }
+}
+
 pub struct Camera {
     // Public
     pub aspect_ratio: f64,      // Image Ratio
     pub image_width: u32,       // Image width
-    pub samples_per_pixel: u32, // Count of random samples for each pixel
+    pub samples_per_pixel: u32, // Count of random samples for each pixel, the most with adaptive
     pub max_depth: u32,         // Max number of bounces
+    pub roulette_depth: u32,    // Bounces before Russian roulette can end a path
+    pub background: Background, // Scene background color
+    pub integrator: Integrator, // Light transport algorithm
+    pub sampler: SamplerKind,   // Where pixel, lens and bounce samples come from
+    pub filter: Filter,         // How samples are spread over the pixels around them
+    pub aovs: Vec<Aov>,         // Passes rendered next to the image
+
+    // Adaptive sampling stops a pixel once the standard error of its mean luminance drops below
+    // this fraction of the mean, 0 turns it off. Pixels always get min_samples_per_pixel first.
+    // What stopped pixels leave of samples_per_pixel goes to the ones that are still noisy, up
+    // to max_samples_per_pixel each, 0 allows four times samples_per_pixel
+    pub adaptive_threshold: f64,
+    pub min_samples_per_pixel: u32,
+    pub max_samples_per_pixel: u32,
+
+    pub samples_per_pass: u32, // Samples added to every pixel per pass, 0 renders in one pass
 
     pub v_fov: f64,       // Vertical view angle (field of view)
     pub lookfrom: Point3, // Camera looking from
     pub lookat: Point3,   // Looking at
     pub vup: Vec3,        // Camera-relative "up" direction
-    
+
     pub defocus_angle: f64, // Variation angle of rays for each pixel
-    pub focus_dist: f64, // Distance from camera lookfrom point to plane of perfect focus
-    
+    pub focus_dist: f64,    // Distance from camera lookfrom point to plane of perfect focus
+
+    pub shutter_open: f64,  // Time the shutter opens at
+    pub shutter_close: f64, // Time the shutter closes at, rays are spread evenly in between
+
+    pub threads: u32,      // Number of render threads, 0 uses all available cores
+    pub seed: Option<u64>, // Fixed random seed, None draws a fresh one for every render
+
     defocus_disk_u: Vec3, // Defocus disck horizontal radius
     defocus_disk_v: Vec3, // Defocus disk vertical radius
 
@@ -33,12 +117,13 @@ pub struct Camera {
     w: Vec3,
 
     // Private
-    image_height: u32,       // Image height
-    center: Point3,          // Camera Center
-    pixel_00_loc: Point3,    // Locaiton of pixel (0,0)
-    pixel_delta_u: Vec3,     // Offset to pixel to the right
-    pixel_delta_v: Vec3,     // Offset to pixel to below
-    pixel_sample_scale: f64, // Collor sample scale for a sum of pixel samples
+    image_height: u32,                 // Image height
+    center: Point3,                    // Camera Center
+    pixel_00_loc: Point3,              // Locaiton of pixel (0,0)
+    pixel_delta_u: Vec3,               // Offset to pixel to the right
+    pixel_delta_v: Vec3,               // Offset to pixel to below
+    render_seed: u64, // Seeds random streams and sample patterns, the camera seed if set
+    material_ids: HashMap<usize, u32>, // Material ID pass numbers, see number_materials
 }
 
 impl Default for Camera {
@@ -48,12 +133,26 @@ impl Default for Camera {
             image_width: 100,
             samples_per_pixel: 10,
             max_depth: 10,
+            roulette_depth: 3,
+            background: Background::Sky,
+            integrator: Integrator::default(),
+            sampler: SamplerKind::default(),
+            filter: Filter::default(),
+            aovs: vec![],
+            adaptive_threshold: 0.0,
+            min_samples_per_pixel: 16,
+            max_samples_per_pixel: 0,
+            samples_per_pass: 0,
             v_fov: 90.0,
             lookfrom: Point3::new(0.0, 0.0, 0.0),
             lookat: Point3::new(0.0, 0.0, -1.0),
             vup: Vec3::new(0.0, 1.0, 0.0),
             defocus_angle: 0.0,
             focus_dist: 10.0,
+            shutter_open: 0.0,
+            shutter_close: 1.0,
+            threads: 0,
+            seed: None,
             defocus_disk_u: Default::default(),
             defocus_disk_v: Default::default(),
             u: Vec3::default(),
@@ -64,45 +163,342 @@ impl Default for Camera {
             pixel_00_loc: Point3::default(),
             pixel_delta_u: Vec3::default(),
             pixel_delta_v: Vec3::default(),
-            pixel_sample_scale: 1.0,
+            render_seed: 0,
+            material_ids: HashMap::new(),
         }
     }
 }
 
 impl Camera {
-    pub fn render(&mut self, world: &HittableList) {
+    // Lights are sampled directly on top of following the materials, they still have to be in
+    // the world as well to be seen. With no lights only the materials are followed.
+    //
+    // Samples are added in passes over the whole image, samples_per_pass at a time, and
+    // `after_pass` gets to see the state after each one. A render can carry on from the state
+    // of an earlier one, as long as the image size and settings match. It keeps the seed of
+    // that render, a camera seed has to be the same one.
+    pub fn render(
+        &mut self,
+        world: &HittableList,
+        lights: &HittableList,
+        resume: Option<RenderState>,
+        mut after_pass: impl FnMut(&RenderState, bool) -> Result<()>,
+    ) -> Result<RenderState> {
         self.initialize();
+        self.number_materials(world);
+
+        let settings = RenderSettings {
+            integrator: self.integrator,
+            sampler: self.sampler,
+            filter: self.filter,
+            samples_per_pixel: self.samples_per_pixel,
+            max_depth: self.max_depth,
+            roulette_depth: self.roulette_depth,
+            adaptive_threshold: self.adaptive_threshold,
+            min_samples_per_pixel: self.min_samples_per_pixel,
+            max_samples_per_pixel: self.max_samples_per_pixel,
+        };
+
+        let mut state = match resume {
+            Some(mut state) => {
+                if (state.width, state.height) != (self.image_width, self.image_height) {
+                    bail!(
+                        "checkpoint is for a {}x{} image, this render is {}x{}",
+                        state.width,
+                        state.height,
+                        self.image_width,
+                        self.image_height
+                    );
+                }
+                if let Some(seed) = self.seed
+                    && seed != state.seed
+                {
+                    bail!(
+                        "checkpoint was rendered with the seed {}, this render asks for {}",
+                        state.seed,
+                        seed
+                    );
+                }
+                if state.aovs != self.aovs {
+                    bail!("checkpoint was rendered with other passes than this render asks for");
+                }
+                if let Some(mismatch) = state.settings.mismatch(&settings) {
+                    bail!(mismatch);
+                }
+                state.settings = settings;
+                self.render_seed = state.seed;
+                state
+            }
+            None => RenderState::new(
+                self.image_width,
+                self.image_height,
+                self.render_seed,
+                settings,
+                self.aovs.clone(),
+            ),
+        };
+
+        // Only shared access from here on, so the camera can be borrowed by every worker
+        let camera = &*self;
+        let thread_count = camera.thread_count();
+        let reach = camera.filter.reach();
+
+        // Adaptive passes past samples_per_pixel are not known up front, they get to count
+        // their own progress
+        let regular_passes = camera.regular_passes(state.samples_done());
+        let mut timer = Mutex::new(Timer::new(
+            (camera.image_height as usize * regular_passes as usize).max(1),
+            1,
+        ));
+        timer.lock().unwrap().wind_up();
+
+        let mut next_target = camera.next_target(&state);
+        while let Some(target) = next_target {
+            if target > camera.samples_per_pixel {
+                let pass_timer = timer.get_mut().unwrap();
+                *pass_timer = Timer::new(camera.image_height as usize, 1);
+                pass_timer.wind_up();
+            }
+
+            // Scanlines are handed out one at a time, so threads that finish early keep picking
+            // up work
+            let next_row = AtomicU32::new(0);
+            let pixels = &state.pixels;
+            let merge = Mutex::new(StripMerge::new(&mut state.films, reach));
+
+            let rows: Vec<(u32, Vec<PixelState>)> = thread::scope(|scope| {
+                let workers: Vec<_> = (0..thread_count)
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let mut rendered = vec![];
+                            loop {
+                                let j = next_row.fetch_add(1, Ordering::Relaxed);
+                                if j >= camera.image_height {
+                                    break;
+                                }
+
+                                let start = (j * camera.image_width) as usize;
+                                let mut row =
+                                    pixels[start..start + camera.image_width as usize].to_vec();
+
+                                // Samples of this row land in the rows the filter reaches too,
+                                // for the image and every pass
+                                let mut strips: Vec<Image> = (0..=camera.aovs.len())
+                                    .map(|_| Image::film(camera.image_width, 2 * reach + 1))
+                                    .collect();
+                                camera.render_row(j, &mut row, &mut strips, target, world, lights);
+                                merge.lock().unwrap().add(j, strips);
+                                rendered.push((j, row));
+                                timer.lock().unwrap().tick();
+                            }
+                            rendered
+                        })
+                    })
+                    .collect();
+
+                workers
+                    .into_iter()
+                    .flat_map(|worker| worker.join().unwrap())
+                    .collect()
+            });
+
+            // Put the scanlines back where they belong
+            for (j, row) in rows {
+                let start = (j * self.image_width) as usize;
+                state.pixels[start..start + row.len()].copy_from_slice(&row);
+            }
+
+            next_target = camera.next_target(&state);
+            after_pass(&state, next_target.is_none())?;
+        }
+
+        Ok(state)
+    }
+
+    // Samples added to every pixel per pass
+    fn pass_step(&self) -> u32 {
+        match self.samples_per_pass {
+            0 => self.samples_per_pixel,
+            step => step,
+        }
+    }
+
+    fn max_samples_per_pixel(&self) -> u32 {
+        match self.max_samples_per_pixel {
+            0 => self.samples_per_pixel.saturating_mul(4),
+            max => max.max(self.samples_per_pixel),
+        }
+    }
 
-        let mut new_data = vec![];
-        new_data.resize_with((self.image_width * self.image_height) as usize, || {
-            Default::default()
-        });
+    // Passes left until every pixel has samples_per_pixel samples or has converged
+    fn regular_passes(&self, done: u32) -> u32 {
+        if done >= self.samples_per_pixel {
+            return 0;
+        }
+        let step = self.pass_step();
+        self.samples_per_pixel.div_ceil(step) - done / step
+    }
+
+    // Sample count the pixels still being sampled are brought up to in the next pass, None
+    // once the render is done. Pixels first go up to samples_per_pixel in steps of a pass.
+    // With adaptive sampling the samples that converged pixels did not take are then shared
+    // out over the ones that have not, a pass at a time, so the image as a whole still takes
+    // no more than samples_per_pixel on average
+    fn next_target(&self, state: &RenderState) -> Option<u32> {
+        let done = state.samples_done();
+        let step = self.pass_step();
+        if done < self.samples_per_pixel {
+            return Some(((done / step + 1) * step).min(self.samples_per_pixel));
+        }
+
+        let max_samples = self.max_samples_per_pixel();
+        if self.adaptive_threshold <= 0.0 || done >= max_samples {
+            return None;
+        }
+
+        let budget = state.pixels.len() as u64 * self.samples_per_pixel as u64;
+        let spent: u64 = state.pixels.iter().map(|pixel| pixel.count as u64).sum();
+        let open = state.pixels.iter().filter(|pixel| !pixel.converged).count() as u64;
+        let share = (budget.saturating_sub(spent) / open).min(step as u64) as u32;
+        if share == 0 {
+            return None;
+        }
+        Some((done + share).min(max_samples))
+    }
+
+    // Brings every pixel of row j up to `target` samples, splatting them into strips of film
+    // centered on the row
+    fn render_row(
+        &self,
+        j: u32,
+        row: &mut [PixelState],
+        strips: &mut [Image],
+        target: u32,
+        world: &HittableList,
+        lights: &HittableList,
+    ) {
+        let mut sampler = self
+            .sampler
+            .create(self.samples_per_pixel, self.render_seed);
+
+        for (i, pixel) in row.iter_mut().enumerate() {
+            self.render_pixel(
+                i as u32,
+                j,
+                pixel,
+                strips,
+                target,
+                world,
+                lights,
+                sampler.as_mut(),
+            );
+        }
+    }
 
-        let mut timer = Timer::new(self.image_height as usize, 1);
-        timer.wind_up();
+    #[allow(clippy::too_many_arguments)]
+    fn render_pixel(
+        &self,
+        i: u32,
+        j: u32,
+        pixel: &mut PixelState,
+        strips: &mut [Image],
+        target: u32,
+        world: &HittableList,
+        lights: &HittableList,
+        sampler: &mut dyn Sampler,
+    ) {
+        let adaptive = self.adaptive_threshold > 0.0;
+        let min_samples = self
+            .min_samples_per_pixel
+            .clamp(2, self.samples_per_pixel.max(2));
+
+        while pixel.count < target && !pixel.converged {
+            let (sample_color, offset, aov_values) =
+                self.render_sample(i, j, pixel.count, world, lights, sampler);
+            self.splat(strips, i, &offset, sample_color, &aov_values);
+            pixel.count += 1;
+
+            if !adaptive {
+                continue;
+            }
+
+            // Running mean and sum of squared deviations of the luminance (Welford's method)
+            let luminance = sample_color.luminance();
+            let delta = luminance - pixel.mean;
+            pixel.mean += delta / pixel.count as f64;
+            pixel.m2 += delta * (luminance - pixel.mean);
+
+            // Dark pixels are judged against a small floor, otherwise they would never settle
+            if pixel.count >= min_samples {
+                let count = pixel.count as f64;
+                let standard_error = f64::sqrt(pixel.m2 / ((count - 1.0) * count));
+                pixel.converged =
+                    standard_error <= self.adaptive_threshold * f64::max(pixel.mean, 0.01);
+            }
+        }
+    }
 
-        // Render
-        for j in 0..self.image_height {
-            timer.tick();
-            for i in 0..self.image_width {
-                let mut pixel_color: Vec3 = Vec3::default();
+    fn render_sample(
+        &self,
+        i: u32,
+        j: u32,
+        sample: u32,
+        world: &HittableList,
+        lights: &HittableList,
+        sampler: &mut dyn Sampler,
+    ) -> (Color, Vec3, Vec<Color>) {
+        // Every sample of every pixel draws from its own random stream, so the image does not
+        // depend on which thread took which pixel, or in what order
+        let pixel_index = j as u64 * self.image_width as u64 + i as u64;
+        seed_rng_stream(hash(&[self.render_seed, sample as u64]), pixel_index);
+
+        sampler.start_pixel_sample(i, j, sample);
+        let offset = Self::sample_square(sampler.get_2d());
+        let r = self.get_ray(i, j, &offset, sampler);
+        let color = match self.integrator {
+            Integrator::Naive => self.ray_color(&r, world, lights, sampler),
+            Integrator::Mis => self.ray_color_mis(&r, world, lights, sampler),
+        };
+
+        (color, offset, self.aov_values(&r, world, sampler))
+    }
 
-                for _ in 0..self.samples_per_pixel {
-                    let r = self.get_ray(i, j);
-                    pixel_color += Self::ray_color(&r, self.max_depth, &world);
+    // Adds a sample taken `offset` away from the center of pixel i to every pixel of the strips
+    // the filter reaches, weighted by its distance to their centers. The first strip gets the
+    // color, the others the pass values
+    fn splat(&self, strips: &mut [Image], i: u32, offset: &Vec3, color: Color, aovs: &[Color]) {
+        let reach = self.filter.reach() as i64;
+
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                let x = i as i64 + dx;
+                if x < 0 || x >= self.image_width as i64 {
+                    continue;
                 }
 
-                new_data[(j * self.image_width + i) as usize] =
-                    pixel_color * self.pixel_sample_scale;
+                let weight = self
+                    .filter
+                    .evaluate(offset.x() - dx as f64, offset.y() - dy as f64);
+                if weight == 0.0 {
+                    continue;
+                }
+                let (x, y) = (x as u32, (dy + reach) as u32);
+                strips[0].splat(x, y, color * weight, weight);
+                for (strip, value) in strips[1..].iter_mut().zip(aovs) {
+                    strip.splat(x, y, *value * weight, weight);
+                }
             }
         }
+    }
 
-        // Save the image
-        let mut new_image = Image::new(self.image_width, self.image_height);
-        new_image.load_data(new_data);
+    fn thread_count(&self) -> u32 {
+        if self.threads > 0 {
+            return self.threads;
+        }
 
-        let ppm_new_image: PPM = new_image.into();
-        ppm_new_image.save("final_render.ppm").unwrap();
+        thread::available_parallelism()
+            .map(|count| count.get() as u32)
+            .unwrap_or(1)
     }
 
     fn initialize(&mut self) {
@@ -110,7 +506,7 @@ impl Camera {
         let image_height = (self.image_width as f64 / self.aspect_ratio) as u32;
         self.image_height = if image_height < 1 { 1 } else { image_height };
 
-        self.pixel_sample_scale = 1.0 / self.samples_per_pixel as f64;
+        self.render_seed = self.seed.unwrap_or_else(rand::random);
 
         self.center = self.lookfrom;
 
@@ -139,44 +535,109 @@ impl Camera {
         let viewport_upper_left =
             self.center - (self.focus_dist * self.w) - viewport_u / 2.0 - viewport_v / 2.0;
         self.pixel_00_loc = viewport_upper_left + 0.5 * (self.pixel_delta_u + self.pixel_delta_v);
-        
+
         // Calcualte camera defocus disk basis vectors
-        let defocus_radius = self.focus_dist * f64::tan(degrees_to_radians(self.defocus_angle / 2.0));
+        let defocus_radius =
+            self.focus_dist * f64::tan(degrees_to_radians(self.defocus_angle / 2.0));
         self.defocus_disk_u = self.u * defocus_radius;
         self.defocus_disk_v = self.v * defocus_radius;
     }
 
-    fn ray_color(r: &Ray, depth: u32, world: &HittableList) -> Color {
-        if depth <= 0 {
-            return Color::default();
-        }
-
-        let mut rec = HitRecord::default();
+    fn ray_color(
+        &self,
+        r: &Ray,
+        world: &HittableList,
+        lights: &HittableList,
+        sampler: &mut dyn Sampler,
+    ) -> Color {
+        let mut color = Color::default();
+        let mut throughput = Color::new(1.0, 1.0, 1.0); // What the path lets through so far
+        let mut r = *r;
+
+        for bounce in 0..self.max_depth {
+            let mut rec = HitRecord::default();
+
+            // If ray missed all of the geometry it "will hit the background"
+            if !world.hit(&r, Interval::new(0.001, f64::INFINITY), &mut rec, sampler) {
+                color += throughput * self.background.color(&r);
+                break;
+            }
 
-        // Go through all object and check if they are hit
-        if world.hit(&r, Interval::new(0.001, INFINITY), &mut rec) {
-            let mut scattered = Ray::default();
-            let mut attenuation = Color::default();
+            let mut srec = ScatterRecord::default();
             let material = rec.mat.as_ref().unwrap();
 
-            if material.scatter(&r, &rec, &mut attenuation, &mut scattered) {
-                return attenuation * Camera::ray_color(&mut scattered, depth - 1, &world);
+            color += throughput * material.emitted(&r, &rec);
+
+            // Half of the scattered rays aim at the lights and pick up most of their light at
+            // the next hit, so the roulette runs here instead of right before that
+            if !self.survives_roulette(bounce, &mut throughput, sampler)
+                || !material.scatter(&r, &rec, sampler.get_1d(), &mut srec)
+            {
+                break;
             }
-            return Color::default();
+
+            // Specular materials already picked the one direction that matters
+            let Some(material_pdf) = srec.pdf.as_deref() else {
+                throughput *= srec.attenuation;
+                r = srec.skip_pdf_ray;
+                continue;
+            };
+
+            // Half of the rays head straight for a light, the other half follow the material
+            let light_pdf = HittablePdf::new(lights, rec.p, r.time());
+            let mixture_pdf = MixturePdf::new(&light_pdf, material_pdf);
+            let pdf: &dyn Pdf = if lights.objects.is_empty() {
+                material_pdf
+            } else {
+                &mixture_pdf
+            };
+
+            let scattered = Ray::with_time(rec.p, pdf.generate(sampler.get_2d()), r.time());
+            let pdf_value = pdf.value(scattered.direction());
+            if pdf_value <= 0.0 {
+                break;
+            }
+
+            // Weight the rest of the path by how likely the material is to scatter that way over
+            // how likely we were to pick it
+            let scattering_pdf = material.scattering_pdf(&r, &rec, &scattered);
+            throughput *= srec.attenuation * scattering_pdf / pdf_value;
+            r = scattered;
         }
 
-        // If ray missed all of the geometry it "will hit the sky" and the color that it should be is this one
-        let unit_direction = Vec3::unit_vector(r.direction());
-        let a = 0.5 * (unit_direction.y() + 1.0);
-        let c = (1.0 - a) * Vec3::new(1.0, 1.0, 1.0) + a * Vec3::new(0.5, 0.7, 1.0);
-        c.into()
+        color
     }
 
-    fn get_ray(&self, i: u32, j: u32) -> Ray {
-        // Construct a camera ray originating from the defocus disk and directed at a randomly
-        // sampled point around the pixel location i, j.
+    // Once a path has made roulette_depth bounces, it goes on with a chance of the largest part
+    // of its throughput and the paths that survive carry the light of the ones that did not.
+    // Dim paths end early without biasing the average, bright ones are never cut off. Both
+    // integrators ask at every hit, after its emission is counted and before it scatters, with
+    // the bounces that led up to the hit.
+    fn survives_roulette(
+        &self,
+        bounces: u32,
+        throughput: &mut Color,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        if bounces < self.roulette_depth {
+            return true;
+        }
 
-        let offset = Self::sample_square();
+        let survival = throughput.max_component();
+        if survival >= 1.0 {
+            return true;
+        }
+        if sampler.get_1d() >= survival {
+            return false;
+        }
+
+        *throughput = *throughput / survival;
+        true
+    }
+
+    fn get_ray(&self, i: u32, j: u32, offset: &Vec3, sampler: &mut dyn Sampler) -> Ray {
+        // Construct a camera ray originating from the defocus disk and directed at the point
+        // `offset` away from the center of pixel i, j.
 
         // Fires a ray somewhere around (i,j) coordinate
         let pixel_sample = self.pixel_00_loc
@@ -184,19 +645,26 @@ impl Camera {
             + ((j as f64 + offset.y()) * self.pixel_delta_v);
 
         // Create Ray
-        let ray_origin = if self.defocus_angle <= 0.0 {self.center} else {self.defocus_disk_sample()};
+        let ray_origin = if self.defocus_angle <= 0.0 {
+            self.center
+        } else {
+            self.defocus_disk_sample(sampler.get_2d())
+        };
         let ray_direction = pixel_sample - ray_origin;
-        Ray::new(ray_origin, ray_direction)
+        let ray_time =
+            self.shutter_open + (self.shutter_close - self.shutter_open) * sampler.get_1d();
+
+        Ray::with_time(ray_origin, ray_direction, ray_time)
     }
-    
-    fn defocus_disk_sample(&self) -> Point3 {
+
+    fn defocus_disk_sample(&self, u: (f64, f64)) -> Point3 {
         // Returns random point in camera defocus disk
-        
-        let p = Vec3::random_in_unit_disc();
+
+        let p = Vec3::sample_unit_disc(u);
         self.center + (p[0] * self.defocus_disk_u) + (p[1] * self.defocus_disk_v)
     }
 
-    fn sample_square() -> Vec3 {
-        Vec3::new(random_double() - 0.5, random_double() - 0.5, 0.0)
+    fn sample_square(u: (f64, f64)) -> Vec3 {
+        Vec3::new(u.0 - 0.5, u.1 - 0.5, 0.0)
     }
 }
@@ -0,0 +1,464 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{image::Image, sampler::SamplerKind, vec3::Color};
+
+use super::{
+    Integrator,
+    aov::Aov,
+    filter::{Filter, FilterKind},
+};
+
+const MAGIC: &[u8; 8] = b"RTCKPT05";
+const STATE_SIZE: usize = 4 + 8 + 8 + 1; // Bytes per pixel for its PixelState
+const FILM_SIZE: usize = 3 * 8 + 8; // Bytes per pixel for every film, the sums and the weight
+
+// Sampling progress of a pixel, enough to carry on sampling it later. What the samples added
+// up to lives in the film, spread over the pixels around it by the filter.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PixelState {
+    pub(super) count: u32, // Samples taken in this pixel
+    pub(super) mean: f64,  // Running mean and squared deviations of the luminance, for adaptive
+    pub(super) m2: f64,
+    pub(super) converged: bool, // Adaptive sampling is done with this pixel
+}
+
+// Camera settings that decide what the samples of a render are and how they are weighted.
+// They are stored with a checkpoint and a render may only resume it with the same ones
+#[derive(Clone, Copy, Debug)]
+pub(super) struct RenderSettings {
+    pub(super) integrator: Integrator,
+    pub(super) sampler: SamplerKind,
+    pub(super) filter: Filter,
+    pub(super) samples_per_pixel: u32,
+    pub(super) max_depth: u32,
+    pub(super) roulette_depth: u32,
+    pub(super) adaptive_threshold: f64,
+    pub(super) min_samples_per_pixel: u32,
+    pub(super) max_samples_per_pixel: u32,
+}
+
+impl RenderSettings {
+    // What differs between the settings a checkpoint was made with and the ones of this
+    // render, if anything. Only the sample count may grow, to refine a render further, unless
+    // the sampler lays out its pattern for the count or adaptive sampling shares it out
+    pub(super) fn mismatch(&self, render: &Self) -> Option<String> {
+        let differs = |what: &str, checkpoint: String, render: String| {
+            Some(format!(
+                "checkpoint was rendered with {} {}, this render asks for {}",
+                what, checkpoint, render
+            ))
+        };
+
+        if self.integrator != render.integrator {
+            return differs(
+                "the integrator",
+                Integrator::NAMES[self.integrator as usize].to_string(),
+                Integrator::NAMES[render.integrator as usize].to_string(),
+            );
+        }
+        if self.sampler != render.sampler {
+            return differs(
+                "the sampler",
+                SamplerKind::NAMES[self.sampler as usize].to_string(),
+                SamplerKind::NAMES[render.sampler as usize].to_string(),
+            );
+        }
+        if self.filter.kind != render.filter.kind || self.filter.radius != render.filter.radius {
+            let describe = |filter: &Filter| {
+                format!(
+                    "{} {}",
+                    FilterKind::NAMES[filter.kind as usize],
+                    filter.radius
+                )
+            };
+            return differs(
+                "the filter",
+                describe(&self.filter),
+                describe(&render.filter),
+            );
+        }
+        if self.max_depth != render.max_depth {
+            return differs(
+                "max depth",
+                self.max_depth.to_string(),
+                render.max_depth.to_string(),
+            );
+        }
+        if self.roulette_depth != render.roulette_depth {
+            return differs(
+                "roulette depth",
+                self.roulette_depth.to_string(),
+                render.roulette_depth.to_string(),
+            );
+        }
+        // The adaptive settings do nothing while adaptive sampling is off on both sides
+        let adaptive = self.adaptive_threshold > 0.0 || render.adaptive_threshold > 0.0;
+        if adaptive
+            && (self.adaptive_threshold != render.adaptive_threshold
+                || self.min_samples_per_pixel != render.min_samples_per_pixel
+                || self.max_samples_per_pixel != render.max_samples_per_pixel)
+        {
+            let describe = |settings: &Self| {
+                let max = match settings.max_samples_per_pixel {
+                    0 => "auto".to_string(),
+                    max => max.to_string(),
+                };
+                format!(
+                    "{} {}-{}",
+                    settings.adaptive_threshold, settings.min_samples_per_pixel, max
+                )
+            };
+            return differs(
+                "the adaptive threshold and samples",
+                describe(self),
+                describe(render),
+            );
+        }
+        if self.samples_per_pixel != render.samples_per_pixel
+            && (self.sampler == SamplerKind::Stratified
+                || self.adaptive_threshold > 0.0
+                || render.samples_per_pixel < self.samples_per_pixel)
+        {
+            return differs(
+                "samples per pixel",
+                self.samples_per_pixel.to_string(),
+                render.samples_per_pixel.to_string(),
+            );
+        }
+        None
+    }
+}
+
+// Accumulated samples of a whole image. Every sample draws from a random stream picked by
+// the seed, the pixel and the sample index, so the seed, the per pixel counts and the
+// settings are all the state there is: resumed from a checkpoint, a render of the same scene
+// ends up bit identical to one that was never interrupted. The scene itself is not stored,
+// changing it in between mixes samples of both.
+pub struct RenderState {
+    pub width: u32,
+    pub height: u32,
+    pub(super) seed: u64,
+    pub(super) settings: RenderSettings,
+    pub(super) pixels: Vec<PixelState>,
+    pub(super) aovs: Vec<Aov>,
+
+    // Filter weighted sums and weight sums, the image first and then one per AOV
+    pub(super) films: Vec<Image>,
+}
+
+impl RenderState {
+    pub(super) fn new(
+        width: u32,
+        height: u32,
+        seed: u64,
+        settings: RenderSettings,
+        aovs: Vec<Aov>,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            seed,
+            settings,
+            pixels: vec![PixelState::default(); (width * height) as usize],
+            films: (0..=aovs.len())
+                .map(|_| Image::film(width, height))
+                .collect(),
+            aovs,
+        }
+    }
+
+    // Filtered average of the samples taken so far, pixels without any are black
+    pub fn image(&self) -> Image {
+        self.films[0].resolve()
+    }
+
+    // The passes rendered next to the image, in the order they were asked for
+    pub fn aov_images(&self) -> Vec<(Aov, Image)> {
+        self.aovs
+            .iter()
+            .zip(&self.films[1..])
+            .map(|(aov, film)| (*aov, film.resolve()))
+            .collect()
+    }
+
+    // Samples per pixel as an image, black took the fewest and white the most, going through
+    // red and yellow
+    pub fn sample_heatmap(&self) -> Image {
+        let counts = self.pixels.iter().map(|pixel| pixel.count);
+        let fewest = counts.clone().min().unwrap_or(0);
+        let most = counts.max().unwrap_or(0);
+        let range = f64::max((most - fewest) as f64, 1.0);
+
+        let data = self
+            .pixels
+            .iter()
+            .map(|pixel| {
+                let t = (pixel.count - fewest) as f64 / range;
+                Color::new(
+                    f64::clamp(3.0 * t, 0.0, 1.0),
+                    f64::clamp(3.0 * t - 1.0, 0.0, 1.0),
+                    f64::clamp(3.0 * t - 2.0, 0.0, 1.0),
+                )
+            })
+            .collect();
+
+        let mut heatmap = Image::new(self.width, self.height);
+        heatmap.load_data(data);
+        heatmap
+    }
+
+    // Fewest samples any pixel has that is still being sampled, once every pixel has converged
+    // there is nothing left to do at any sample count
+    pub fn samples_done(&self) -> u32 {
+        self.pixels
+            .iter()
+            .filter(|pixel| !pixel.converged)
+            .map(|pixel| pixel.count)
+            .min()
+            .unwrap_or(u32::MAX)
+    }
+
+    // Written next to the target first and renamed over it, so a render killed while saving
+    // still leaves the previous checkpoint intact
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&self.width.to_le_bytes())?;
+        writer.write_all(&self.height.to_le_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+
+        let settings = &self.settings;
+        writer.write_all(&[
+            settings.integrator as u8,
+            settings.sampler as u8,
+            settings.filter.kind as u8,
+        ])?;
+        writer.write_all(&settings.filter.radius.to_le_bytes())?;
+        writer.write_all(&settings.samples_per_pixel.to_le_bytes())?;
+        writer.write_all(&settings.max_depth.to_le_bytes())?;
+        writer.write_all(&settings.roulette_depth.to_le_bytes())?;
+        writer.write_all(&settings.adaptive_threshold.to_le_bytes())?;
+        writer.write_all(&settings.min_samples_per_pixel.to_le_bytes())?;
+        writer.write_all(&settings.max_samples_per_pixel.to_le_bytes())?;
+
+        writer.write_all(&(self.aovs.len() as u32).to_le_bytes())?;
+        for aov in &self.aovs {
+            writer.write_all(&[aov.index() as u8])?;
+        }
+
+        for (index, pixel) in self.pixels.iter().enumerate() {
+            writer.write_all(&pixel.count.to_le_bytes())?;
+            writer.write_all(&pixel.mean.to_le_bytes())?;
+            writer.write_all(&pixel.m2.to_le_bytes())?;
+            writer.write_all(&[pixel.converged as u8])?;
+
+            let (x, y) = (index as u32 % self.width, index as u32 / self.width);
+            for film in &self.films {
+                for channel in film.pixel(x, y).e {
+                    writer.write_all(&channel.to_le_bytes())?;
+                }
+                writer.write_all(&film.weight(x, y).to_le_bytes())?;
+            }
+        }
+
+        writer.into_inner()?.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path).with_context(|| format!("Could not read {}", path.display()))?;
+
+        let mut reader = Reader { bytes: &bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            bail!("{} is not a render checkpoint", path.display());
+        }
+
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        let seed = reader.u64()?;
+
+        let unknown = || anyhow!("checkpoint {} has unknown render settings", path.display());
+        let [integrator, sampler, filter] = reader.take(3)? else {
+            unreachable!()
+        };
+        let integrator = Integrator::NAMES
+            .get(*integrator as usize)
+            .and_then(|name| Integrator::from_name(name))
+            .ok_or_else(unknown)?;
+        let sampler = SamplerKind::NAMES
+            .get(*sampler as usize)
+            .and_then(|name| SamplerKind::from_name(name))
+            .ok_or_else(unknown)?;
+        let filter = FilterKind::NAMES
+            .get(*filter as usize)
+            .and_then(|name| FilterKind::from_name(name))
+            .ok_or_else(unknown)?;
+        let settings = RenderSettings {
+            integrator,
+            sampler,
+            filter: Filter::new(filter).with_radius(reader.f64()?),
+            samples_per_pixel: reader.u32()?,
+            max_depth: reader.u32()?,
+            roulette_depth: reader.u32()?,
+            adaptive_threshold: reader.f64()?,
+            min_samples_per_pixel: reader.u32()?,
+            max_samples_per_pixel: reader.u32()?,
+        };
+
+        let aov_count = reader.u32()? as usize;
+        let mut aovs = Vec::with_capacity(aov_count.min(Aov::ALL.len()));
+        for _ in 0..aov_count {
+            let index = reader.take(1)?[0] as usize;
+            let Some(aov) = Aov::ALL.get(index) else {
+                bail!("checkpoint {} has an unknown pass", path.display());
+            };
+            aovs.push(*aov);
+        }
+
+        // Checked against the file before anything is allocated for the pixels
+        let pixel_count = width as usize * height as usize;
+        let pixel_size = STATE_SIZE + (aov_count + 1) * FILM_SIZE;
+        if pixel_count.checked_mul(pixel_size) != Some(reader.bytes.len()) {
+            bail!(
+                "checkpoint {} is truncated or corrupt, expected {} pixels",
+                path.display(),
+                pixel_count
+            );
+        }
+
+        let mut state = Self::new(width, height, seed, settings, aovs);
+        for index in 0..pixel_count {
+            state.pixels[index] = PixelState {
+                count: reader.u32()?,
+                mean: reader.f64()?,
+                m2: reader.f64()?,
+                converged: reader.take(1)?[0] != 0,
+            };
+
+            let (x, y) = (index as u32 % width, index as u32 / width);
+            for film in &mut state.films {
+                let sum = Color::new(reader.f64()?, reader.f64()?, reader.f64()?);
+                film.splat(x, y, sum, reader.f64()?);
+            }
+        }
+
+        Ok(state)
+    }
+}
+
+// Adds the film strips of finished scanlines to the films strictly in row order, whichever
+// thread finished first, so pixels that several rows splat into always sum up the same way
+pub(super) struct StripMerge<'a> {
+    films: &'a mut [Image],
+    reach: u32, // Rows a strip reaches above and below its own
+    next: u32,  // Next row to add
+    pending: BTreeMap<u32, Vec<Image>>,
+}
+
+impl<'a> StripMerge<'a> {
+    pub(super) fn new(films: &'a mut [Image], reach: u32) -> Self {
+        Self {
+            films,
+            reach,
+            next: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    // One strip per film, in the same order
+    pub(super) fn add(&mut self, row: u32, strips: Vec<Image>) {
+        self.pending.insert(row, strips);
+        while let Some(strips) = self.pending.remove(&self.next) {
+            let top = self.next as i64 - self.reach as i64;
+            for (film, strip) in self.films.iter_mut().zip(&strips) {
+                film.accumulate(strip, top);
+            }
+            self.next += 1;
+        }
+    }
+}
+
+// Little endian fields read off the front of a byte slice
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < count {
+            bail!("checkpoint ends early");
+        }
+        let (head, tail) = self.bytes.split_at(count);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, process};
+
+    use crate::{camera::Camera, scene::builtin};
+
+    use super::*;
+
+    fn assert_same_image(a: &Image, b: &Image) {
+        assert_eq!((a.width(), a.height()), (b.width(), b.height()));
+        for y in 0..a.height() {
+            for x in 0..a.width() {
+                assert_eq!(a.pixel(x, y).e, b.pixel(x, y).e, "pixel {} {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn resuming_at_more_samples_matches_a_fresh_render() {
+        let scene = builtin::by_name("cornell_box").unwrap();
+        let mut camera = scene.camera;
+        camera.image_width = 24;
+        camera.samples_per_pixel = 8;
+        camera.samples_per_pass = 4;
+        camera.max_depth = 8;
+        camera.threads = 2;
+        camera.seed = Some(3);
+
+        let path = std::env::temp_dir().join(format!("resume-{}.ckpt", process::id()));
+        let render = |camera: &mut Camera, resume| {
+            camera
+                .render(&scene.world, &scene.lights, resume, |_, _| Ok(()))
+                .unwrap()
+        };
+
+        render(&mut camera, None).save(&path).unwrap();
+        let checkpoint = RenderState::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        camera.samples_per_pixel = 16;
+        let resumed = render(&mut camera, Some(checkpoint));
+        let fresh = render(&mut camera, None);
+        assert_same_image(&resumed.image(), &fresh.image());
+    }
+}
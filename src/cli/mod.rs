@@ -0,0 +1,437 @@
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::{
+    camera::{
+        Camera, Integrator,
+        aov::Aov,
+        filter::{Filter, FilterKind},
+    },
+    image::{
+        Format,
+        display::{DisplayTransform, ToneMap},
+    },
+    sampler::SamplerKind,
+    scene::builtin,
+    utility::parse_ratio,
+};
+
+pub const USAGE: &str = "\
+Usage: Ray-Tracing [OPTIONS] [SCENE]
+
+Options:
+  -s, --scene <SCENE>          Built-in scene name or path to a scene file [default: first_book_finale]
+  -o, --output <PATH>          Where to write the rendered image [default: final_render.ppm]
+  -f, --format <FORMAT>        Output format (ppm, ppm-binary, png, hdr, pfm), guessed from the
+                               output extension when omitted
+      --alpha                  Also write how much of every pixel the scene covers as alpha
+                               channel, for formats that support it (png)
+      --exposure <STOPS>       Brighten or darken the 8 bit output, +1 doubles the radiance
+      --tone-map <NAME>        Curve for highlights in 8 bit output (clamp, reinhard,
+                               reinhard-extended, aces, agx) [default: clamp]
+      --white-point <VALUE>    Radiance that turns white with reinhard-extended [default: 4]
+  -w, --width <PIXELS>         Image width
+  -a, --aspect-ratio <RATIO>   Width over height, as a number or a fraction such as 16/9
+  -n, --spp <COUNT>            Samples per pixel, on average over the image with --adaptive
+      --adaptive <THRESHOLD>   Stop sampling a pixel once its relative standard error drops
+                               below the threshold, such as 0.02, and spend the samples it
+                               saved on pixels that are still noisy
+      --min-spp <COUNT>        Samples every pixel takes before --adaptive can stop it
+      --max-spp <COUNT>        Most samples a noisy pixel can get with --adaptive [default:
+                               four times --spp]
+      --heatmap <PATH>         Also write an image of how many samples each pixel took
+      --aov <NAME>[=<PATH>]    Also write a pass from the first hit of every camera ray (depth,
+                               normal, albedo, position, material-id, object-id, coverage),
+                               next to the output unless a path is given. Can be repeated, use
+                               .pfm files to keep negative and unclamped values
+      --pass-spp <COUNT>       Samples added to every pixel per progressive pass
+      --checkpoint <PATH>      Periodically save the render progress to this file
+      --checkpoint-interval <SECONDS>
+                               Least time between checkpoint saves [default: 60]
+      --resume                 Carry on from the checkpoint instead of starting over
+  -d, --max-depth <COUNT>      Maximum number of ray bounces
+      --roulette-depth <COUNT> Bounces before Russian roulette can end a path, at least
+                               max-depth turns it off
+  -i, --integrator <NAME>      Light transport algorithm (naive, mis)
+      --sampler <NAME>         Sample pattern (independent, stratified, halton, sobol)
+      --filter <NAME>          Pixel reconstruction filter (box, tent, gaussian, mitchell, lanczos)
+      --filter-radius <PIXELS> Filter radius, each filter has its own default
+  -t, --threads <COUNT>        Render threads, 0 uses all available cores
+      --seed <SEED>            Seed for reproducible renders
+  -h, --help                   Print this message";
+
+pub enum Command {
+    Render(Box<Args>),
+    Help,
+}
+
+// Render settings given on the command line, anything left as None keeps the scene default
+pub struct Args {
+    pub scene: String,
+    pub output: String,
+    pub format: Format,
+    pub alpha: bool,
+    pub display: DisplayTransform,
+    pub heatmap: Option<(String, Format)>,
+    pub aovs: Vec<(Aov, String, Format)>,
+    pub checkpoint: Option<String>,
+    pub checkpoint_interval: u64,
+    pub resume: bool,
+    pub width: Option<u32>,
+    pub aspect_ratio: Option<f64>,
+    pub samples_per_pixel: Option<u32>,
+    pub adaptive_threshold: Option<f64>,
+    pub min_samples_per_pixel: Option<u32>,
+    pub max_samples_per_pixel: Option<u32>,
+    pub samples_per_pass: Option<u32>,
+    pub max_depth: Option<u32>,
+    pub roulette_depth: Option<u32>,
+    pub integrator: Option<Integrator>,
+    pub sampler: Option<SamplerKind>,
+    pub filter: Option<FilterKind>,
+    pub filter_radius: Option<f64>,
+    pub threads: Option<u32>,
+    pub seed: Option<u64>,
+}
+
+impl Args {
+    pub fn writes_alpha(&self) -> bool {
+        self.alpha && self.format == Format::Png
+    }
+
+    // Command line settings take priority over whatever the scene asked for
+    pub fn apply(&self, camera: &mut Camera) {
+        if let Some(width) = self.width {
+            camera.image_width = width;
+        }
+        if let Some(aspect_ratio) = self.aspect_ratio {
+            camera.aspect_ratio = aspect_ratio;
+        }
+        if let Some(samples_per_pixel) = self.samples_per_pixel {
+            camera.samples_per_pixel = samples_per_pixel;
+        }
+        if let Some(adaptive_threshold) = self.adaptive_threshold {
+            camera.adaptive_threshold = adaptive_threshold;
+        }
+        if let Some(min_samples_per_pixel) = self.min_samples_per_pixel {
+            camera.min_samples_per_pixel = min_samples_per_pixel;
+        }
+        if let Some(max_samples_per_pixel) = self.max_samples_per_pixel {
+            camera.max_samples_per_pixel = max_samples_per_pixel;
+        }
+        if let Some(samples_per_pass) = self.samples_per_pass {
+            camera.samples_per_pass = samples_per_pass;
+        } else if self.checkpoint.is_some() && camera.samples_per_pass == 0 {
+            // A single pass would only ever checkpoint a finished render
+            camera.samples_per_pass = 16;
+        }
+        if let Some(max_depth) = self.max_depth {
+            camera.max_depth = max_depth;
+        }
+        if let Some(roulette_depth) = self.roulette_depth {
+            camera.roulette_depth = roulette_depth;
+        }
+        if let Some(integrator) = self.integrator {
+            camera.integrator = integrator;
+        }
+        if let Some(sampler) = self.sampler {
+            camera.sampler = sampler;
+        }
+        if let Some(filter) = self.filter {
+            camera.filter = Filter::new(filter);
+        }
+        if let Some(radius) = self.filter_radius {
+            camera.filter = camera.filter.with_radius(radius);
+        }
+        if !self.aovs.is_empty() {
+            camera.aovs = self.aovs.iter().map(|(aov, _, _)| *aov).collect();
+        }
+        if self.writes_alpha() && !camera.aovs.contains(&Aov::Coverage) {
+            // The alpha channel is the coverage pass, rendered even when it is not written out
+            camera.aovs.push(Aov::Coverage);
+        }
+        if let Some(threads) = self.threads {
+            camera.threads = threads;
+        }
+        if self.seed.is_some() {
+            camera.seed = self.seed;
+        }
+    }
+}
+
+// Parses the arguments that follow the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command> {
+    let mut scene = None;
+    let mut output = None;
+    let mut format = None;
+    let mut alpha = false;
+    let mut display = DisplayTransform::default();
+    let mut width = None;
+    let mut aspect_ratio = None;
+    let mut samples_per_pixel = None;
+    let mut adaptive_threshold = None;
+    let mut min_samples_per_pixel = None;
+    let mut max_samples_per_pixel = None;
+    let mut heatmap = None;
+    let mut aov_args = vec![];
+    let mut samples_per_pass = None;
+    let mut checkpoint = None;
+    let mut checkpoint_interval = 60;
+    let mut resume = false;
+    let mut max_depth = None;
+    let mut roulette_depth = None;
+    let mut integrator = None;
+    let mut sampler = None;
+    let mut filter = None;
+    let mut filter_radius = None;
+    let mut threads = None;
+    let mut seed = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // Both `--option value` and `--option=value` are accepted
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| anyhow!("option '{}' requires a value", flag))
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-s" | "--scene" => scene = Some(value()?),
+            "-o" | "--output" => output = Some(value()?),
+            "-f" | "--format" => {
+                let name = value()?;
+                format = Some(Format::from_name(&name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown output format '{}', expected one of: {}",
+                        name,
+                        Format::NAMES.join(", ")
+                    )
+                })?);
+            }
+            "--alpha" => alpha = true,
+            "--exposure" => {
+                let text = value()?;
+                display.exposure = number(&flag, &text)?;
+                if !display.exposure.is_finite() {
+                    bail!("'{}' has to be a finite number of stops", flag);
+                }
+            }
+            "--tone-map" => {
+                let name = value()?;
+                display.tone_map = ToneMap::from_name(&name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown tone map '{}', expected one of: {}",
+                        name,
+                        ToneMap::NAMES.join(", ")
+                    )
+                })?;
+            }
+            "--white-point" => {
+                let text = value()?;
+                display.white_point = number(&flag, &text)?;
+                if !(display.white_point > 0.0 && display.white_point.is_finite()) {
+                    bail!("'{}' has to be a positive number", flag);
+                }
+            }
+            "-w" | "--width" => width = Some(positive(&flag, &value()?)?),
+            "-a" | "--aspect-ratio" => {
+                let text = value()?;
+                aspect_ratio = Some(parse_ratio(&text).ok_or_else(|| {
+                    anyhow!(
+                        "invalid value '{}' for '{}': expected a positive number or fraction",
+                        text,
+                        flag
+                    )
+                })?);
+            }
+            "-n" | "--spp" => samples_per_pixel = Some(positive(&flag, &value()?)?),
+            "--adaptive" => {
+                let text = value()?;
+                let threshold: f64 = number(&flag, &text)?;
+                if !(threshold >= 0.0 && threshold.is_finite()) {
+                    bail!("'{}' has to be a non-negative number", flag);
+                }
+                adaptive_threshold = Some(threshold);
+            }
+            "--min-spp" => min_samples_per_pixel = Some(positive(&flag, &value()?)?),
+            "--max-spp" => max_samples_per_pixel = Some(positive(&flag, &value()?)?),
+            "--heatmap" => heatmap = Some(value()?),
+            "--aov" => aov_args.push(value()?),
+            "--pass-spp" => samples_per_pass = Some(positive(&flag, &value()?)?),
+            "--checkpoint" => checkpoint = Some(value()?),
+            "--checkpoint-interval" => checkpoint_interval = number(&flag, &value()?)?,
+            "--resume" => resume = true,
+            "-d" | "--max-depth" => max_depth = Some(number(&flag, &value()?)?),
+            "--roulette-depth" => roulette_depth = Some(number(&flag, &value()?)?),
+            "-i" | "--integrator" => {
+                let name = value()?;
+                integrator = Some(Integrator::from_name(&name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown integrator '{}', expected one of: {}",
+                        name,
+                        Integrator::NAMES.join(", ")
+                    )
+                })?);
+            }
+            "--sampler" => {
+                let name = value()?;
+                sampler = Some(SamplerKind::from_name(&name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown sampler '{}', expected one of: {}",
+                        name,
+                        SamplerKind::NAMES.join(", ")
+                    )
+                })?);
+            }
+            "--filter" => {
+                let name = value()?;
+                filter = Some(FilterKind::from_name(&name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown filter '{}', expected one of: {}",
+                        name,
+                        FilterKind::NAMES.join(", ")
+                    )
+                })?);
+            }
+            "--filter-radius" => {
+                let radius: f64 = number(&flag, &value()?)?;
+                if !(radius > 0.0 && radius.is_finite()) {
+                    bail!("'{}' has to be a positive number", flag);
+                }
+                filter_radius = Some(radius);
+            }
+            "-t" | "--threads" => threads = Some(number(&flag, &value()?)?),
+            "--seed" => seed = Some(number(&flag, &value()?)?),
+            _ if flag.starts_with('-') && flag.len() > 1 => bail!("unknown option '{}'", flag),
+            // A bare argument is the scene, so `Ray-Tracing my.scene` keeps working
+            _ if scene.is_none() => scene = Some(arg),
+            _ => bail!("unexpected argument '{}'", arg),
+        }
+    }
+
+    let output = output.unwrap_or_else(|| "final_render.ppm".to_string());
+    let format = match format {
+        Some(format) => format,
+        None => Format::from_path(&output).ok_or_else(|| {
+            anyhow!(
+                "can not tell the output format from '{}', pass --format ({})",
+                output,
+                Format::NAMES.join(", ")
+            )
+        })?,
+    };
+
+    let heatmap = match heatmap {
+        Some(path) => {
+            let format = Format::from_path(&path).ok_or_else(|| {
+                anyhow!(
+                    "can not tell the heatmap format from '{}', use a .ppm, .png, .hdr or .pfm file",
+                    path
+                )
+            })?;
+            Some((path, format))
+        }
+        None => None,
+    };
+
+    let mut aovs: Vec<(Aov, String, Format)> = vec![];
+    for text in aov_args {
+        let (name, path) = match text.split_once('=') {
+            Some((name, path)) => (name, Some(path.to_string())),
+            None => (text.as_str(), None),
+        };
+        let aov = Aov::from_name(name).ok_or_else(|| {
+            anyhow!(
+                "unknown pass '{}', expected one of: {}",
+                name,
+                Aov::NAMES.join(", ")
+            )
+        })?;
+        if aovs.iter().any(|(other, _, _)| *other == aov) {
+            bail!("pass '{}' is asked for more than once", name);
+        }
+
+        // Without a path the pass goes next to the output, in the same format
+        let (path, format) = match path {
+            Some(path) => {
+                let format = Format::from_path(&path).ok_or_else(|| {
+                    anyhow!(
+                        "can not tell the format of the {} pass from '{}', use a .ppm, .png, \
+                         .hdr or .pfm file",
+                        name,
+                        path
+                    )
+                })?;
+                (path, format)
+            }
+            None => {
+                let extension = format!("{}.{}", aov.name(), format.extension());
+                let path = Path::new(&output).with_extension(extension);
+                (path.to_string_lossy().into_owned(), format)
+            }
+        };
+        aovs.push((aov, path, format));
+    }
+
+    if resume && checkpoint.is_none() {
+        bail!("'--resume' needs a '--checkpoint' file to resume from");
+    }
+
+    Ok(Command::Render(Box::new(Args {
+        scene: scene.unwrap_or_else(|| builtin::NAMES[0].to_string()),
+        output,
+        format,
+        alpha,
+        display,
+        heatmap,
+        aovs,
+        checkpoint,
+        checkpoint_interval,
+        resume,
+        width,
+        aspect_ratio,
+        samples_per_pixel,
+        adaptive_threshold,
+        min_samples_per_pixel,
+        max_samples_per_pixel,
+        samples_per_pass,
+        max_depth,
+        roulette_depth,
+        integrator,
+        sampler,
+        filter,
+        filter_radius,
+        threads,
+        seed,
+    })))
+}
+
+fn number<T>(flag: &str, text: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    text.parse()
+        .map_err(|err| anyhow!("invalid value '{}' for '{}': {}", text, flag, err))
+}
+
+fn positive(flag: &str, text: &str) -> Result<u32> {
+    let value: u32 = number(flag, text)?;
+    if value == 0 {
+        bail!("'{}' has to be at least 1", flag);
+    }
+    Ok(value)
+}
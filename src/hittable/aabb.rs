@@ -0,0 +1,128 @@
+use crate::{
+    ray::Ray,
+    utility::interval::{self, Interval},
+    vec3::Point3,
+};
+
+// Axis-aligned bounding box, stored as one interval per axis
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Aabb {
+    pub x: Interval,
+    pub y: Interval,
+    pub z: Interval,
+}
+
+pub const EMPTY: Aabb = Aabb {
+    x: interval::EMPTY,
+    y: interval::EMPTY,
+    z: interval::EMPTY,
+};
+
+impl Aabb {
+    pub fn new(x: Interval, y: Interval, z: Interval) -> Self {
+        Self { x, y, z }
+    }
+
+    // Treat the two points a and b as extrema for the bounding box, so we don't require a
+    // particular minimum/maximum coordinate order
+    pub fn from_points(a: &Point3, b: &Point3) -> Self {
+        let span = |n: usize| {
+            if a[n] <= b[n] {
+                Interval::new(a[n], b[n])
+            } else {
+                Interval::new(b[n], a[n])
+            }
+        };
+
+        Self::new(span(0), span(1), span(2))
+    }
+
+    // Smallest box that contains both of the given boxes
+    pub fn enclosing(box0: &Aabb, box1: &Aabb) -> Self {
+        Self {
+            x: Interval::enclosing(&box0.x, &box1.x),
+            y: Interval::enclosing(&box0.y, &box1.y),
+            z: Interval::enclosing(&box0.z, &box1.z),
+        }
+    }
+
+    // Adjust the box so that no side is narrower than some delta, padding if necessary.
+    // Flat primitives such as quads would otherwise get a box with zero thickness
+    pub fn pad_to_minimums(mut self) -> Self {
+        let delta = 0.0001;
+        if self.x.size() < delta {
+            self.x = self.x.expand(delta);
+        }
+        if self.y.size() < delta {
+            self.y = self.y.expand(delta);
+        }
+        if self.z.size() < delta {
+            self.z = self.z.expand(delta);
+        }
+        self
+    }
+
+    pub fn axis_interval(&self, n: usize) -> &Interval {
+        match n {
+            1 => &self.y,
+            2 => &self.z,
+            _ => &self.x,
+        }
+    }
+
+    // Slab test, the ray is clipped against each pair of axis planes in turn
+    pub fn hit(&self, r: &Ray, mut ray_t: Interval) -> bool {
+        let ray_orig = r.origin();
+        let ray_dir = r.direction();
+
+        for axis in 0..3 {
+            let ax = self.axis_interval(axis);
+            let adinv = 1.0 / ray_dir[axis];
+
+            let t0 = (ax.min - ray_orig[axis]) * adinv;
+            let t1 = (ax.max - ray_orig[axis]) * adinv;
+
+            if t0 < t1 {
+                ray_t.min = f64::max(t0, ray_t.min);
+                ray_t.max = f64::min(t1, ray_t.max);
+            } else {
+                ray_t.min = f64::max(t1, ray_t.min);
+                ray_t.max = f64::min(t0, ray_t.max);
+            }
+
+            if ray_t.max <= ray_t.min {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    // Index of the axis with the largest extent
+    pub fn longest_axis(&self) -> usize {
+        if self.x.size() > self.y.size() {
+            if self.x.size() > self.z.size() { 0 } else { 2 }
+        } else if self.y.size() > self.z.size() {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        Point3::new(
+            0.5 * (self.x.min + self.x.max),
+            0.5 * (self.y.min + self.y.max),
+            0.5 * (self.z.min + self.z.max),
+        )
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let (dx, dy, dz) = (self.x.size(), self.y.size(), self.z.size());
+        if dx < 0.0 || dy < 0.0 || dz < 0.0 {
+            return 0.0;
+        }
+
+        2.0 * (dx * dy + dy * dz + dz * dx)
+    }
+}
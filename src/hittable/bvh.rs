@@ -0,0 +1,184 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use crate::{ray::Ray, sampler::Sampler, utility::interval::Interval};
+
+use super::{
+    HitRecord, Hittable,
+    aabb::{self, Aabb},
+    hittable_list::HittableList,
+    material::Material,
+};
+
+// Number of buckets the centroids are binned into when evaluating SAH splits
+const SAH_BUCKETS: usize = 12;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitMethod {
+    // Sort along the longest axis and cut the list in half
+    Median,
+    // Pick the cut that minimizes the surface area heuristic cost
+    Sah,
+}
+
+pub struct BvhNode {
+    left: Arc<dyn Hittable>,
+    right: Arc<dyn Hittable>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    pub fn new(list: HittableList, split: SplitMethod) -> Self {
+        let mut objects = list.objects;
+        Self::build(&mut objects, split)
+    }
+
+    fn build(objects: &mut [Arc<dyn Hittable>], split: SplitMethod) -> Self {
+        // Build the bounding box of the span of source objects
+        let bbox = objects.iter().fold(aabb::EMPTY, |bbox, object| {
+            Aabb::enclosing(&bbox, &object.bounding_box())
+        });
+
+        match objects.len() {
+            0 => {
+                // Nothing to hit, both children point to an empty list
+                let empty: Arc<dyn Hittable> = Arc::new(HittableList::default());
+                return Self {
+                    left: empty.clone(),
+                    right: empty,
+                    bbox,
+                };
+            }
+            1 => {
+                return Self {
+                    left: objects[0].clone(),
+                    right: objects[0].clone(),
+                    bbox,
+                };
+            }
+            2 => {
+                return Self {
+                    left: objects[0].clone(),
+                    right: objects[1].clone(),
+                    bbox,
+                };
+            }
+            _ => {}
+        }
+
+        let (axis, mid) = match split {
+            SplitMethod::Median => Self::median_split(objects, &bbox),
+            SplitMethod::Sah => Self::sah_split(objects, &bbox),
+        };
+
+        objects.sort_by(|a, b| Self::centroid_compare(a, b, axis));
+        let (left, right) = objects.split_at_mut(mid);
+
+        let left: Arc<dyn Hittable> = Arc::new(Self::build(left, split));
+        let right: Arc<dyn Hittable> = Arc::new(Self::build(right, split));
+
+        Self { left, right, bbox }
+    }
+
+    fn median_split(objects: &[Arc<dyn Hittable>], bbox: &Aabb) -> (usize, usize) {
+        (bbox.longest_axis(), objects.len() / 2)
+    }
+
+    // Returns the axis to sort on and how many objects go to the left child
+    fn sah_split(objects: &[Arc<dyn Hittable>], bbox: &Aabb) -> (usize, usize) {
+        let centroid_bounds = objects.iter().fold(aabb::EMPTY, |bounds, object| {
+            let c = object.bounding_box().centroid();
+            Aabb::enclosing(&bounds, &Aabb::from_points(&c, &c))
+        });
+
+        let axis = centroid_bounds.longest_axis();
+        let extent = *centroid_bounds.axis_interval(axis);
+
+        // All centroids sit on top of each other, no plane can separate them
+        if extent.size() <= 0.0 {
+            return Self::median_split(objects, bbox);
+        }
+
+        let bucket_of = |object: &Arc<dyn Hittable>| {
+            let c = object.bounding_box().centroid()[axis];
+            let b = (SAH_BUCKETS as f64 * (c - extent.min) / extent.size()) as usize;
+            b.min(SAH_BUCKETS - 1)
+        };
+
+        let mut counts = [0usize; SAH_BUCKETS];
+        let mut bounds = [aabb::EMPTY; SAH_BUCKETS];
+        for object in objects {
+            let b = bucket_of(object);
+            counts[b] += 1;
+            bounds[b] = Aabb::enclosing(&bounds[b], &object.bounding_box());
+        }
+
+        // Cost of splitting after bucket i, relative to the area of the parent box
+        let mut best_cost = f64::INFINITY;
+        let mut best_split = 0;
+        for i in 0..SAH_BUCKETS - 1 {
+            let (mut left_box, mut right_box) = (aabb::EMPTY, aabb::EMPTY);
+            let (mut left_count, mut right_count) = (0, 0);
+
+            for b in 0..=i {
+                left_box = Aabb::enclosing(&left_box, &bounds[b]);
+                left_count += counts[b];
+            }
+            for b in i + 1..SAH_BUCKETS {
+                right_box = Aabb::enclosing(&right_box, &bounds[b]);
+                right_count += counts[b];
+            }
+
+            let cost = left_count as f64 * left_box.surface_area()
+                + right_count as f64 * right_box.surface_area();
+
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = i;
+            }
+        }
+
+        let mid: usize = counts[..=best_split].iter().sum();
+        if mid == 0 || mid == objects.len() {
+            return Self::median_split(objects, bbox);
+        }
+
+        (axis, mid)
+    }
+
+    fn centroid_compare(a: &Arc<dyn Hittable>, b: &Arc<dyn Hittable>, axis: usize) -> Ordering {
+        let a_center = a.bounding_box().centroid()[axis];
+        let b_center = b.bounding_box().centroid()[axis];
+        a_center.total_cmp(&b_center)
+    }
+}
+
+impl Hittable for BvhNode {
+    fn hit(
+        &self,
+        r: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        if !self.bbox.hit(r, ray_t) {
+            return false;
+        }
+
+        let hit_left = self.left.hit(r, ray_t, rec, sampler);
+
+        // If the left child was hit, the right one only matters if it is closer
+        let right_t = Interval::new(ray_t.min, if hit_left { rec.t } else { ray_t.max });
+        let hit_right = self.right.hit(r, right_t, rec, sampler);
+
+        hit_left || hit_right
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        self.left.materials(visit);
+        self.right.materials(visit);
+    }
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use crate::{
+    ray::Ray,
+    sampler::Sampler,
+    utility::interval::{self, Interval},
+    vec3::{Color, Vec3},
+};
+
+use super::{
+    HitRecord, Hittable,
+    aabb::Aabb,
+    material::{Material, isotropic::Isotropic},
+};
+
+// Volume of uniform density inside a boundary, like smoke or fog. The boundary has to be
+// closed and convex, rays are assumed to enter and leave it at most once
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable>,
+    neg_inv_density: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Arc<dyn Hittable>, density: f64, albedo: Color) -> Self {
+        Self::with_phase_function(boundary, density, Arc::new(Isotropic::new(albedo)))
+    }
+
+    pub fn with_phase_function(
+        boundary: Arc<dyn Hittable>,
+        density: f64,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase_function,
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(
+        &self,
+        r: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        // Where the ray enters and leaves the boundary, the ray may also start inside it
+        let mut rec1 = HitRecord::default();
+        let mut rec2 = HitRecord::default();
+
+        if !self.boundary.hit(r, interval::UNIVERSE, &mut rec1, sampler) {
+            return false;
+        }
+        if !self.boundary.hit(
+            r,
+            Interval::new(rec1.t + 0.0001, f64::INFINITY),
+            &mut rec2,
+            sampler,
+        ) {
+            return false;
+        }
+
+        rec1.t = rec1.t.max(ray_t.min);
+        rec2.t = rec2.t.min(ray_t.max);
+        if rec1.t >= rec2.t {
+            return false;
+        }
+        rec1.t = rec1.t.max(0.0);
+
+        // Sample the free flight distance, the probability of scattering within a small
+        // distance is proportional to the density, so distances are exponentially distributed
+        let ray_length = r.direction().length();
+        let distance_inside_boundary = (rec2.t - rec1.t) * ray_length;
+        let hit_distance = self.neg_inv_density * sampler.get_1d().ln();
+
+        if hit_distance > distance_inside_boundary {
+            return false;
+        }
+
+        rec.t = rec1.t + hit_distance / ray_length;
+        rec.p = r.at(rec.t);
+
+        // The normal and facing are meaningless inside a volume, set them to something valid
+        rec.normal = Vec3::new(1.0, 0.0, 0.0);
+        rec.front_face = true;
+        rec.u = 0.0;
+        rec.v = 0.0;
+        rec.mat = Some(self.phase_function.clone());
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        // The boundary is never seen, only what scatters inside it
+        visit(&self.phase_function);
+    }
+}
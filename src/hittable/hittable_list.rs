@@ -1,39 +1,80 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::utility::interval::Interval;
+use crate::{
+    sampler::Sampler,
+    utility::interval::Interval,
+    vec3::{Point3, Vec3},
+};
 
-use super::{HitRecord, Hittable};
+use super::{
+    HitRecord, Hittable,
+    aabb::{self, Aabb},
+    material::Material,
+    tagged::Tagged,
+};
 
-#[derive(Default)]
 pub struct HittableList {
-    pub objects: Vec<Rc<dyn Hittable>>,
+    pub objects: Vec<Arc<dyn Hittable>>,
+    bbox: Aabb,
 }
 
-impl HittableList {
-    pub fn new(object: Rc<dyn Hittable>) -> Self {
-        let mut n = vec![];
-        n.push(object);
+impl Default for HittableList {
+    fn default() -> Self {
+        Self {
+            objects: vec![],
+            bbox: aabb::EMPTY,
+        }
+    }
+}
 
-        Self { objects: n }
+impl HittableList {
+    pub fn new(object: Arc<dyn Hittable>) -> Self {
+        let mut list = Self::default();
+        list.add(object);
+        list
     }
 
+    #[allow(dead_code)]
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.bbox = aabb::EMPTY;
     }
 
-    pub fn add(&mut self, object: Rc<dyn Hittable>) {
+    pub fn add(&mut self, object: Arc<dyn Hittable>) {
+        self.bbox = Aabb::enclosing(&self.bbox, &object.bounding_box());
         self.objects.push(object);
     }
+
+    // Gives every object its position in the list as object id. Only meant for the list a
+    // scene is declared in, before it goes into a BVH
+    pub fn tag_objects(self) -> Self {
+        let mut tagged = Self::default();
+        for (index, object) in self.objects.into_iter().enumerate() {
+            tagged.add(Arc::new(Tagged::new(object, index as u32)));
+        }
+        tagged
+    }
 }
 
 impl Hittable for HittableList {
-    fn hit(&self, r: &crate::ray::Ray, ray_t: Interval, rec: &mut super::HitRecord) -> bool {
+    fn hit(
+        &self,
+        r: &crate::ray::Ray,
+        ray_t: Interval,
+        rec: &mut super::HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
         let mut temp_rec: HitRecord = HitRecord::default();
         let mut hit_anything = false;
         let mut closest_so_far = ray_t.max;
 
         for object in &self.objects {
-            if object.hit(&r, Interval::new(ray_t.min, closest_so_far), &mut temp_rec) {
+            if object.hit(
+                r,
+                Interval::new(ray_t.min, closest_so_far),
+                &mut temp_rec,
+                sampler,
+            ) {
                 hit_anything = true;
                 closest_so_far = temp_rec.t;
 
@@ -42,6 +83,42 @@ impl Hittable for HittableList {
             }
         }
 
-        return hit_anything;
+        hit_anything
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        for object in &self.objects {
+            object.materials(visit);
+        }
+    }
+
+    // Every object is picked with the same probability
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects
+            .iter()
+            .map(|object| weight * object.pdf_value(origin, direction, time))
+            .sum()
+    }
+
+    // The first sample coordinate picks the object, what is left of it is reused by the object
+    fn random(&self, origin: &Point3, time: f64, u: (f64, f64)) -> Vec3 {
+        let size = self.objects.len();
+        if size == 0 {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+
+        let scaled = u.0 * size as f64;
+        let index = (scaled as usize).min(size - 1);
+        let remainder = (scaled - index as f64).clamp(0.0, 1.0);
+        self.objects[index].random(origin, time, (remainder, u.1))
     }
 }
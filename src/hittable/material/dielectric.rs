@@ -1,10 +1,10 @@
 use crate::{
+    hittable::HitRecord,
     ray::Ray,
-    utility::random_double,
     vec3::{Color, Vec3},
 };
 
-use super::Material;
+use super::{Material, ScatterRecord};
 
 pub struct Dielectric {
     // Refractive index in vacuum or air, or the ratio of the material's refractive index over
@@ -27,14 +27,14 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(
-        &self,
-        r_in: &crate::ray::Ray,
-        rec: &crate::hittable::HitRecord,
-        attenuation: &mut crate::vec3::Color,
-        scattered: &mut crate::ray::Ray,
-    ) -> bool {
-        *attenuation = Color::new(1.0, 1.0, 1.0);
+    // Clear glass lets everything through
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        Color::new(1.0, 1.0, 1.0)
+    }
+
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, u: f64, srec: &mut ScatterRecord) -> bool {
+        srec.attenuation = Color::new(1.0, 1.0, 1.0);
+        srec.pdf = None;
         let ri = if rec.front_face {
             1.0 / self.refraction_index
         } else {
@@ -48,13 +48,13 @@ impl Material for Dielectric {
 
         let cannot_refract = ri * sin_theta > 1.0;
 
-        let direction = if cannot_refract || (Self::reflectance(cos_theta, ri) > random_double()) {
+        let direction = if cannot_refract || (Self::reflectance(cos_theta, ri) > u) {
             Vec3::reflect(&unit_direction, &rec.normal)
         } else {
             Vec3::refract(&unit_direction, &rec.normal, ri)
         };
 
-        *scattered = Ray::new(rec.p, direction);
+        srec.skip_pdf_ray = Ray::with_time(rec.p, direction, r_in.time());
         true
     }
 }
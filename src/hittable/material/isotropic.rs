@@ -0,0 +1,42 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    hittable::HitRecord,
+    pdf::sphere::SpherePdf,
+    ray::Ray,
+    texture::{Texture, solid_color::SolidColor},
+    vec3::Color,
+};
+
+use super::{Material, ScatterRecord};
+
+// Phase function of a participating medium, scatters the same amount in every direction
+pub struct Isotropic {
+    tex: Arc<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(color: Color) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    pub fn from_texture(tex: Arc<dyn Texture>) -> Self {
+        Self { tex }
+    }
+}
+
+impl Material for Isotropic {
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.tex.value(rec.u, rec.v, &rec.p)
+    }
+
+    fn scatter(&self, _r_in: &Ray, rec: &HitRecord, _u: f64, srec: &mut ScatterRecord) -> bool {
+        srec.attenuation = self.tex.value(rec.u, rec.v, &rec.p);
+        srec.pdf = Some(Box::new(SpherePdf));
+        true
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
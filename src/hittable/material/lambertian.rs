@@ -1,36 +1,42 @@
+use std::{f64::consts::PI, sync::Arc};
+
 use crate::{
+    hittable::HitRecord,
+    pdf::cosine::CosinePdf,
     ray::Ray,
+    texture::{Texture, solid_color::SolidColor},
     vec3::{Color, Vec3},
 };
 
-use super::Material;
+use super::{Material, ScatterRecord};
 
 pub struct Lambertian {
-    albedo: Color,
+    tex: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(color: Color) -> Self {
-        Self { albedo: color }
+        Self::from_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    pub fn from_texture(tex: Arc<dyn Texture>) -> Self {
+        Self { tex }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(
-        &self,
-        r_in: &crate::ray::Ray,
-        rec: &crate::hittable::HitRecord,
-        attenuation: &mut Color,
-        scattered: &mut crate::ray::Ray,
-    ) -> bool {
-        let mut scatter_direction = rec.normal + Vec3::random_unit_vector();
-
-        if scatter_direction.near_zero() {
-            scatter_direction = rec.normal;
-        }
-
-        *scattered = Ray::new(rec.p, scatter_direction);
-        *attenuation = self.albedo;
-        return true;
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.tex.value(rec.u, rec.v, &rec.p)
+    }
+
+    fn scatter(&self, _r_in: &Ray, rec: &HitRecord, _u: f64, srec: &mut ScatterRecord) -> bool {
+        srec.attenuation = self.tex.value(rec.u, rec.v, &rec.p);
+        srec.pdf = Some(Box::new(CosinePdf::new(&rec.normal)));
+        true
+    }
+
+    fn scattering_pdf(&self, _r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        let cos_theta = Vec3::dot(&rec.normal, &Vec3::unit_vector(scattered.direction()));
+        f64::max(0.0, cos_theta / PI)
     }
 }
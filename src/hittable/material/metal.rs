@@ -1,39 +1,63 @@
+use std::sync::Arc;
+
 use crate::{
+    hittable::HitRecord,
+    pdf::{Pdf, glossy::GlossyPdf},
     ray::Ray,
+    texture::{Texture, solid_color::SolidColor},
     vec3::{Color, Vec3},
 };
 
-use super::Material;
+use super::{Material, ScatterRecord};
 
 pub struct Metal {
-    albedo: Color,
+    tex: Arc<dyn Texture>,
     fuzz_factor: f64,
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzz_factor: f64) -> Self {
+        Self::from_texture(Arc::new(SolidColor::new(albedo)), fuzz_factor)
+    }
+
+    pub fn from_texture(tex: Arc<dyn Texture>, fuzz_factor: f64) -> Self {
         let fuzz = if fuzz_factor < 1.0 { fuzz_factor } else { 1.0 };
 
         Self {
-            albedo: albedo,
+            tex,
             fuzz_factor: fuzz,
         }
     }
 }
 
 impl Material for Metal {
-    fn scatter(
-        &self,
-        r_in: &crate::ray::Ray,
-        rec: &crate::hittable::HitRecord,
-        attenuation: &mut crate::vec3::Color,
-        scattered: &mut crate::ray::Ray,
-    ) -> bool {
-        let reflected = Vec3::reflect(&r_in.direction(), &rec.normal);
-        let reflected =
-            Vec3::unit_vector(&reflected) + (self.fuzz_factor * Vec3::random_unit_vector());
-        *scattered = Ray::new(rec.p, reflected);
-        *attenuation = self.albedo;
-        Vec3::dot(&scattered.direction(), &rec.normal) > 0.0
+    fn albedo(&self, rec: &HitRecord) -> Color {
+        self.tex.value(rec.u, rec.v, &rec.p)
+    }
+
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, _u: f64, srec: &mut ScatterRecord) -> bool {
+        let reflected = Vec3::reflect(r_in.direction(), &rec.normal);
+        srec.attenuation = self.tex.value(rec.u, rec.v, &rec.p);
+
+        // Fuzzy metal spreads its reflection over a lobe that can be sampled like any other
+        // density, a perfect mirror only has the one direction
+        if self.fuzz_factor > 0.0 {
+            srec.pdf = Some(Box::new(GlossyPdf::new(&reflected, self.fuzz_factor)));
+            return true;
+        }
+
+        srec.pdf = None;
+        srec.skip_pdf_ray = Ray::with_time(rec.p, reflected, r_in.time());
+        Vec3::dot(&reflected, &rec.normal) > 0.0
+    }
+
+    // Fuzzed directions that end up below the surface are absorbed
+    fn scattering_pdf(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        if Vec3::dot(scattered.direction(), &rec.normal) <= 0.0 {
+            return 0.0;
+        }
+
+        let reflected = Vec3::reflect(r_in.direction(), &rec.normal);
+        GlossyPdf::new(&reflected, self.fuzz_factor).value(scattered.direction())
     }
 }
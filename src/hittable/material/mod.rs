@@ -1,19 +1,53 @@
-use crate::{ray::Ray, vec3::Color};
+use crate::{pdf::Pdf, ray::Ray, vec3::Color};
 
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
 
 use super::HitRecord;
 
-pub trait Material {
-    fn scatter(
-        &self,
-        r_in: &Ray,
-        rec: &HitRecord,
-        attenuation: &mut Color,
-        scattered: &mut Ray,
-    ) -> bool {
-        return false;
+// What a material does with an incoming ray. Diffuse materials hand out the density their
+// scattered directions follow, so the integrator can mix it with light sampling. Specular
+// ones have no useful density and pick the one scattered ray themselves
+pub struct ScatterRecord {
+    pub attenuation: Color,
+    pub pdf: Option<Box<dyn Pdf>>, // None for specular scattering, then skip_pdf_ray is used
+    pub skip_pdf_ray: Ray,
+}
+
+impl Default for ScatterRecord {
+    fn default() -> Self {
+        Self {
+            attenuation: Color::new(1.0, 1.0, 1.0),
+            pdf: None,
+            skip_pdf_ray: Ray::default(),
+        }
+    }
+}
+
+// Send + Sync is required so materials can be shared between render threads
+pub trait Material: Send + Sync {
+    // Base color of the surface at the hit point for the albedo pass, without any lighting.
+    // Materials that only emit have none
+    fn albedo(&self, _rec: &HitRecord) -> Color {
+        Color::default()
+    }
+
+    // `u` is a sample in [0, 1) for materials that pick between ways to scatter themselves
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord, _u: f64, _srec: &mut ScatterRecord) -> bool {
+        false
+    }
+
+    // Density of scattering from r_in into the scattered direction, for the materials that
+    // return a pdf from `scatter`
+    fn scattering_pdf(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
+
+    // Radiance given off by the surface at the hit point, most materials do not emit any
+    fn emitted(&self, _r_in: &Ray, _rec: &HitRecord) -> Color {
+        Color::default()
     }
 }
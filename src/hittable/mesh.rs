@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use crate::{
+    ray::Ray,
+    sampler::Sampler,
+    utility::interval::Interval,
+    vec3::{Point3, Vec3},
+};
+
+use super::{
+    HitRecord, Hittable,
+    aabb::Aabb,
+    bvh::{BvhNode, SplitMethod},
+    hittable_list::HittableList,
+    material::Material,
+    triangle::Triangle,
+};
+
+// Vertex attributes shared by all triangles of a mesh
+#[derive(Default)]
+pub struct MeshBuffers {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(f64, f64)>,
+}
+
+// One triangle of a mesh, as indices into the mesh buffers
+pub struct Face {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+    pub mat: Arc<dyn Material>,
+}
+
+// Triangle mesh, its triangles are kept in their own BVH
+pub struct Mesh {
+    bvh: BvhNode,
+}
+
+impl Mesh {
+    pub fn new(buffers: MeshBuffers, faces: Vec<Face>) -> Self {
+        let buffers = Arc::new(buffers);
+
+        let mut triangles = HittableList::default();
+        for face in faces {
+            triangles.add(Arc::new(Triangle::from_buffers(
+                buffers.clone(),
+                face.positions,
+                face.normals,
+                face.uvs,
+                face.mat,
+            )));
+        }
+
+        Self {
+            bvh: BvhNode::new(triangles, SplitMethod::Sah),
+        }
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit(
+        &self,
+        r: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        self.bvh.hit(r, ray_t, rec, sampler)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh.bounding_box()
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        self.bvh.materials(visit);
+    }
+}
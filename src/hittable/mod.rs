@@ -1,16 +1,25 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
+use aabb::Aabb;
 use material::Material;
-use sphere::Sphere;
 
 use crate::{
     ray::Ray,
+    sampler::Sampler,
     utility::interval::Interval,
     vec3::{Point3, Vec3},
 };
 
+pub mod aabb;
+pub mod bvh;
+pub mod constant_medium;
 pub mod hittable_list;
+pub mod mesh;
+pub mod quad;
 pub mod sphere;
+pub mod tagged;
+pub mod transform;
+pub mod triangle;
 
 pub mod material;
 
@@ -18,9 +27,12 @@ pub mod material;
 pub struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
-    pub mat: Option<Rc<dyn Material>>,
+    pub mat: Option<Arc<dyn Material>>,
     pub t: f64,
+    pub u: f64, // Surface coordinates of the hit point
+    pub v: f64,
     pub front_face: bool,
+    pub object_id: u32, // Id of the scene object that was hit, see Tagged
 }
 
 impl HitRecord {
@@ -39,6 +51,26 @@ impl HitRecord {
     }
 }
 
-pub trait Hittable {
-    fn hit(&self, r: &Ray, ray_t: Interval, rec: &mut HitRecord) -> bool;
+// Send + Sync is required so the world can be shared between render threads
+pub trait Hittable: Send + Sync {
+    // Volumes draw how far the ray gets into them from the sampler, surfaces do not use it
+    fn hit(&self, r: &Ray, ray_t: Interval, rec: &mut HitRecord, sampler: &mut dyn Sampler)
+    -> bool;
+
+    fn bounding_box(&self) -> Aabb;
+
+    // Density over directions from origin that `random` samples with, for a ray leaving at
+    // `time`. Only geometry that can be sampled as a light implements these two
+    fn pdf_value(&self, _origin: &Point3, _direction: &Vec3, _time: f64) -> f64 {
+        0.0
+    }
+
+    // Direction from origin towards the object, picked by a sample from [0, 1)^2
+    fn random(&self, _origin: &Point3, _time: f64, _u: (f64, f64)) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    // Calls visit with every material the object is made of, containers pass it on to their
+    // children. Used to number the materials for the material ID pass
+    fn materials(&self, _visit: &mut dyn FnMut(&Arc<dyn Material>)) {}
 }
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use crate::{
+    ray::Ray,
+    sampler::{Sampler, independent::IndependentSampler},
+    utility::interval::Interval,
+    vec3::{Point3, Vec3},
+};
+
+use super::{HitRecord, Hittable, aabb::Aabb, hittable_list::HittableList, material::Material};
+
+// Parallelogram spanned by the edges u and v starting at corner q
+pub struct Quad {
+    q: Point3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3, // Cached n / (n . n), used to find the planar coordinates of a hit
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+    normal: Vec3,
+    d: f64, // Plane equation is n . p = d
+    area: f64,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, mat: Arc<dyn Material>) -> Self {
+        let n = Vec3::cross(&u, &v);
+        let normal = Vec3::unit_vector(&n);
+
+        // Compute the bounding box of all four vertices
+        let bbox_diagonal1 = Aabb::from_points(&q, &(q + u + v));
+        let bbox_diagonal2 = Aabb::from_points(&(q + u), &(q + v));
+
+        Self {
+            q,
+            u,
+            v,
+            w: n / Vec3::dot(&n, &n),
+            mat,
+            bbox: Aabb::enclosing(&bbox_diagonal1, &bbox_diagonal2).pad_to_minimums(),
+            normal,
+            d: Vec3::dot(&normal, &q),
+            area: n.length(),
+        }
+    }
+
+    // Given the hit point in plane coordinates, return false if it is outside the primitive
+    fn is_interior(a: f64, b: f64) -> bool {
+        let unit_interval = Interval::new(0.0, 1.0);
+        unit_interval.contains(a) && unit_interval.contains(b)
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(
+        &self,
+        r: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        _sampler: &mut dyn Sampler,
+    ) -> bool {
+        let denom = Vec3::dot(&self.normal, r.direction());
+
+        // No hit if the ray is parallel to the plane
+        if f64::abs(denom) < 1e-8 {
+            return false;
+        }
+
+        // Return false if the hit point parameter t is outside the ray interval
+        let t = (self.d - Vec3::dot(&self.normal, r.origin())) / denom;
+        if !ray_t.contains(t) {
+            return false;
+        }
+
+        // Determine if the hit point lies within the planar shape using its plane coordinates
+        let intersection = r.at(t);
+        let planar_hitpt_vector = intersection - self.q;
+        let alpha = Vec3::dot(&self.w, &Vec3::cross(&planar_hitpt_vector, &self.v));
+        let beta = Vec3::dot(&self.w, &Vec3::cross(&self.u, &planar_hitpt_vector));
+
+        if !Self::is_interior(alpha, beta) {
+            return false;
+        }
+
+        // Ray hits the 2D shape, set the rest of the hit record and return true
+        rec.t = t;
+        rec.p = intersection;
+        rec.u = alpha;
+        rec.v = beta;
+        rec.mat = Some(self.mat.clone());
+        rec.set_face_normal(r, &self.normal);
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        visit(&self.mat);
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let mut rec = HitRecord::default();
+        if !self.hit(
+            &Ray::with_time(*origin, *direction, time),
+            Interval::new(0.001, f64::INFINITY),
+            &mut rec,
+            &mut IndependentSampler,
+        ) {
+            return 0.0;
+        }
+
+        // Uniform over the area, converted to a density over solid angle
+        let distance_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = f64::abs(Vec3::dot(direction, &rec.normal) / direction.length());
+
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: &Point3, _time: f64, u: (f64, f64)) -> Vec3 {
+        let p = self.q + (u.0 * self.u) + (u.1 * self.v);
+        p - *origin
+    }
+}
+
+// Returns the 3D box (six sides) that contains the two opposite vertices a & b
+pub fn make_box(a: &Point3, b: &Point3, mat: Arc<dyn Material>) -> HittableList {
+    let mut sides = HittableList::default();
+
+    // Construct the two opposite vertices with the minimum and maximum coordinates
+    let min = Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z()));
+    let max = Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z()));
+
+    let dx = Vec3::new(max.x() - min.x(), 0.0, 0.0);
+    let dy = Vec3::new(0.0, max.y() - min.y(), 0.0);
+    let dz = Vec3::new(0.0, 0.0, max.z() - min.z());
+
+    // Edges are ordered so every normal points out of the box
+    let faces = [
+        (Point3::new(min.x(), min.y(), max.z()), dx, dy), // front
+        (Point3::new(max.x(), min.y(), max.z()), -dz, dy), // right
+        (Point3::new(max.x(), min.y(), min.z()), -dx, dy), // back
+        (Point3::new(min.x(), min.y(), min.z()), dz, dy), // left
+        (Point3::new(min.x(), max.y(), max.z()), dx, -dz), // top
+        (Point3::new(min.x(), min.y(), min.z()), dx, dz), // bottom
+    ];
+
+    for (q, u, v) in faces {
+        sides.add(Arc::new(Quad::new(q, u, v, mat.clone())));
+    }
+
+    sides
+}
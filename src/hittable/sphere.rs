@@ -1,32 +1,82 @@
-use std::rc::Rc;
+use std::{f64::consts::PI, sync::Arc};
 
 use crate::{
+    ray::Ray,
+    sampler::{Sampler, independent::IndependentSampler},
     utility::interval::Interval,
-    vec3::{Point3, Vec3},
+    vec3::{Point3, Vec3, onb::Onb},
 };
 
-use super::{HitRecord, Hittable, material::Material};
+use super::{HitRecord, Hittable, aabb::Aabb, material::Material};
 
 #[derive(Default, Clone)]
 pub struct Sphere {
-    center: Point3,
+    center: Ray, // Path of the center, at(0) is where it starts and at(1) where it ends
     radius: f64,
-    mat: Option<Rc<dyn Material>>,
+    mat: Option<Arc<dyn Material>>,
+    bbox: Aabb,
 }
 
 impl Sphere {
-    pub fn new(center: Point3, radius: f64, mat: Rc<dyn Material>) -> Self {
+    // Stationary sphere
+    pub fn new(center: Point3, radius: f64, mat: Arc<dyn Material>) -> Self {
+        Self::new_moving(center, center, radius, mat)
+    }
+
+    // Sphere moving in a straight line from center1 at time 0 to center2 at time 1, outside
+    // of that interval it rests at the nearest end
+    pub fn new_moving(
+        center1: Point3,
+        center2: Point3,
+        radius: f64,
+        mat: Arc<dyn Material>,
+    ) -> Self {
+        let radius = f64::max(radius, 0.0);
+        let rvec = Vec3::new(radius, radius, radius);
+
+        // The box has to cover the sphere at both ends of its path
+        let box1 = Aabb::from_points(&(center1 - rvec), &(center1 + rvec));
+        let box2 = Aabb::from_points(&(center2 - rvec), &(center2 + rvec));
+
         Self {
-            center,
-            radius: f64::max(radius, 0.0),
+            center: Ray::new(center1, center2 - center1),
+            radius,
             mat: Some(mat),
+            bbox: Aabb::enclosing(&box1, &box2),
         }
     }
+
+    fn center_at(&self, time: f64) -> Point3 {
+        self.center.at(time.clamp(0.0, 1.0))
+    }
+}
+
+impl Sphere {
+    fn get_sphere_uv(p: &Point3) -> (f64, f64) {
+        // p: a given point on the sphere of radius one, centered at the origin.
+        // u: returned value [0,1] of angle around the Y axis from X=-1.
+        // v: returned value [0,1] of angle from Y=-1 to Y=+1.
+        //     <1 0 0> yields <0.50 0.50>       <-1  0  0> yields <0.00 0.50>
+        //     <0 1 0> yields <0.50 1.00>       < 0 -1  0> yields <0.50 0.00>
+        //     <0 0 1> yields <0.25 0.50>       < 0  0 -1> yields <0.75 0.50>
+
+        let theta = f64::acos(-p.y());
+        let phi = f64::atan2(-p.z(), p.x()) + PI;
+
+        (phi / (2.0 * PI), theta / PI)
+    }
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, r: &crate::ray::Ray, ray_t: Interval, rec: &mut HitRecord) -> bool {
-        let oc = self.center - *r.origin();
+    fn hit(
+        &self,
+        r: &crate::ray::Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        _sampler: &mut dyn Sampler,
+    ) -> bool {
+        let current_center = self.center_at(r.time());
+        let oc = current_center - *r.origin();
         let a = r.direction().length_squared();
         let h = Vec3::dot(r.direction(), &oc);
         let c = oc.length_squared() - self.radius * self.radius;
@@ -50,10 +100,57 @@ impl Hittable for Sphere {
         // Update hit record
         rec.t = root;
         rec.p = r.at(rec.t);
-        let outward_normal = (rec.p - self.center) / self.radius;
-        rec.set_face_normal(&r, &outward_normal);
+        let outward_normal = (rec.p - current_center) / self.radius;
+        rec.set_face_normal(r, &outward_normal);
+        (rec.u, rec.v) = Self::get_sphere_uv(&outward_normal);
         rec.mat = self.mat.clone();
 
-        return true;
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        if let Some(mat) = &self.mat {
+            visit(mat);
+        }
+    }
+
+    // A moving sphere is sampled where it is at the time of the ray towards it
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let mut rec = HitRecord::default();
+        if !self.hit(
+            &Ray::with_time(*origin, *direction, time),
+            Interval::new(0.001, f64::INFINITY),
+            &mut rec,
+            &mut IndependentSampler,
+        ) {
+            return 0.0;
+        }
+
+        let distance_squared = (self.center_at(time) - *origin).length_squared();
+        if distance_squared <= self.radius * self.radius {
+            // From inside every direction hits, see `random`
+            return 1.0 / (4.0 * PI);
+        }
+
+        // Uniform over the cone of directions that hit the sphere
+        let cos_theta_max = f64::sqrt(1.0 - self.radius * self.radius / distance_squared);
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: &Point3, time: f64, u: (f64, f64)) -> Vec3 {
+        let direction = self.center_at(time) - *origin;
+        let distance_squared = direction.length_squared();
+        if distance_squared <= self.radius * self.radius {
+            return Vec3::sample_unit_sphere(u);
+        }
+
+        let uvw = Onb::new(&direction);
+        uvw.transform(&Vec3::sample_to_sphere(self.radius, distance_squared, u))
     }
 }
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use crate::{
+    ray::Ray,
+    sampler::Sampler,
+    utility::interval::Interval,
+    vec3::{Point3, Vec3},
+};
+
+use super::{HitRecord, Hittable, aabb::Aabb, material::Material};
+
+// Object of a scene with the id the object ID pass shows for it. Scenes tag their top level
+// objects once, so the ids do not depend on how lists and BVHs are nested below or above them
+pub struct Tagged {
+    object: Arc<dyn Hittable>,
+    id: u32,
+}
+
+impl Tagged {
+    pub fn new(object: Arc<dyn Hittable>, id: u32) -> Self {
+        Self { object, id }
+    }
+}
+
+impl Hittable for Tagged {
+    fn hit(
+        &self,
+        r: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        if !self.object.hit(r, ray_t, rec, sampler) {
+            return false;
+        }
+
+        rec.object_id = self.id;
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.object.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        self.object.pdf_value(origin, direction, time)
+    }
+
+    fn random(&self, origin: &Point3, time: f64, u: (f64, f64)) -> Vec3 {
+        self.object.random(origin, time, u)
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        self.object.materials(visit);
+    }
+}
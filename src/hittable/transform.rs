@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+    ray::Ray,
+    sampler::Sampler,
+    utility::interval::Interval,
+    vec3::{Point3, Vec3, mat3::Mat3},
+};
+
+use super::{
+    HitRecord, Hittable,
+    aabb::{self, Aabb},
+    material::Material,
+};
+
+// Places a shared object in the world with an affine transform. Rays are moved into object
+// space for the intersection and the hit point and normal are moved back out, so one object
+// can be instanced many times without copying its geometry.
+//
+// Operations are applied in the order they are chained, `.scale(..).rotate_y(..).translate(..)`
+// scales the object in place, then rotates it about the origin, then moves it.
+pub struct Transform {
+    object: Arc<dyn Hittable>,
+    linear: Mat3,        // Object to world, without the translation
+    inverse: Mat3,       // World to object
+    normal_matrix: Mat3, // Inverse transpose, keeps normals perpendicular under non-uniform scale
+    offset: Vec3,
+    bbox: Aabb,
+}
+
+impl Transform {
+    pub fn new(object: Arc<dyn Hittable>) -> Self {
+        let bbox = object.bounding_box();
+
+        Self {
+            object,
+            linear: Mat3::identity(),
+            inverse: Mat3::identity(),
+            normal_matrix: Mat3::identity(),
+            offset: Vec3::default(),
+            bbox,
+        }
+    }
+
+    pub fn translate(mut self, offset: Vec3) -> Self {
+        self.offset += offset;
+        self.update()
+    }
+
+    pub fn rotate(self, axis: &Vec3, degrees: f64) -> Self {
+        // Rotations are orthonormal, the transpose is the inverse
+        let rotation = Mat3::rotation(axis, degrees);
+        self.apply(rotation, rotation.transpose())
+    }
+
+    pub fn rotate_x(self, degrees: f64) -> Self {
+        self.rotate(&Vec3::new(1.0, 0.0, 0.0), degrees)
+    }
+
+    pub fn rotate_y(self, degrees: f64) -> Self {
+        self.rotate(&Vec3::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    pub fn rotate_z(self, degrees: f64) -> Self {
+        self.rotate(&Vec3::new(0.0, 0.0, 1.0), degrees)
+    }
+
+    // Per axis scale factors, fails if the object would be flattened
+    pub fn scale(self, factors: Vec3) -> Result<Self> {
+        let scale = Mat3::from_diagonal(&factors);
+        let inverse = scale
+            .inverse()
+            .ok_or_else(|| anyhow!("scale factors can not be zero"))?;
+        Ok(self.apply(scale, inverse))
+    }
+
+    // Applies a linear map on top of everything so far, including the translation. The
+    // inverse is kept up to date alongside, so it never has to be recovered from a product
+    // that may have lost precision
+    fn apply(mut self, m: Mat3, m_inverse: Mat3) -> Self {
+        self.linear = m * self.linear;
+        self.inverse = self.inverse * m_inverse;
+        self.offset = m * self.offset;
+        self.update()
+    }
+
+    fn update(mut self) -> Self {
+        self.normal_matrix = self.inverse.transpose();
+
+        // Box around the eight transformed corners of the object box
+        let object_box = self.object.bounding_box();
+        self.bbox = aabb::EMPTY;
+        if object_box.surface_area() <= 0.0 && object_box.x.min > object_box.x.max {
+            // Nothing to hit, keep the box empty instead of transforming infinities
+            return self;
+        }
+        for i in 0..8 {
+            let corner = Point3::new(
+                if i & 1 == 0 {
+                    object_box.x.min
+                } else {
+                    object_box.x.max
+                },
+                if i & 2 == 0 {
+                    object_box.y.min
+                } else {
+                    object_box.y.max
+                },
+                if i & 4 == 0 {
+                    object_box.z.min
+                } else {
+                    object_box.z.max
+                },
+            );
+            let p = self.to_world(&corner);
+            self.bbox = Aabb::enclosing(&self.bbox, &Aabb::from_points(&p, &p));
+        }
+
+        self
+    }
+
+    fn to_world(&self, p: &Point3) -> Point3 {
+        self.linear * *p + self.offset
+    }
+}
+
+impl Hittable for Transform {
+    fn hit(
+        &self,
+        r: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        sampler: &mut dyn Sampler,
+    ) -> bool {
+        // Transform the ray from world space to object space. The direction is not normalized,
+        // so distances along the ray stay the same and t can be used as is
+        let origin = self.inverse * (*r.origin() - self.offset);
+        let direction = self.inverse * *r.direction();
+        let object_r = Ray::with_time(origin, direction, r.time());
+
+        // Determine whether an intersection exists in object space (and if so, where)
+        if !self.object.hit(&object_r, ray_t, rec, sampler) {
+            return false;
+        }
+
+        // Transform the intersection from object space back to world space
+        rec.p = self.to_world(&rec.p);
+        rec.normal = Vec3::unit_vector(&(self.normal_matrix * rec.normal));
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        self.object.materials(visit);
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let object_origin = self.inverse * (*origin - self.offset);
+        let object_direction = self.inverse * Vec3::unit_vector(direction);
+
+        // Unless the transform is a rotation with a uniform scale, solid angles differ between
+        // the two spaces, the Jacobian of mapping world directions into object space fixes that
+        let jacobian = f64::abs(self.inverse.determinant()) / object_direction.length().powi(3);
+
+        self.object
+            .pdf_value(&object_origin, &object_direction, time)
+            * jacobian
+    }
+
+    fn random(&self, origin: &Point3, time: f64, u: (f64, f64)) -> Vec3 {
+        let object_origin = self.inverse * (*origin - self.offset);
+        self.linear * self.object.random(&object_origin, time, u)
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use crate::{
+    ray::Ray,
+    sampler::{Sampler, independent::IndependentSampler},
+    utility::interval::Interval,
+    vec3::{Point3, Vec3},
+};
+
+use super::{HitRecord, Hittable, aabb::Aabb, material::Material, mesh::MeshBuffers};
+
+// Triangle whose vertex attributes live in a buffer that can be shared with the rest of a mesh
+pub struct Triangle {
+    buffers: Arc<MeshBuffers>,
+    positions: [usize; 3],
+    normals: Option<[usize; 3]>, // Per vertex normals for smooth shading
+    uvs: Option<[usize; 3]>,     // Per vertex texture coordinates
+    mat: Arc<dyn Material>,
+    bbox: Aabb,
+}
+
+impl Triangle {
+    // Standalone triangle that owns its three vertices
+    pub fn new(a: Point3, b: Point3, c: Point3, mat: Arc<dyn Material>) -> Self {
+        let buffers = Arc::new(MeshBuffers {
+            positions: vec![a, b, c],
+            ..Default::default()
+        });
+
+        Self::from_buffers(buffers, [0, 1, 2], None, None, mat)
+    }
+
+    pub fn from_buffers(
+        buffers: Arc<MeshBuffers>,
+        positions: [usize; 3],
+        normals: Option<[usize; 3]>,
+        uvs: Option<[usize; 3]>,
+        mat: Arc<dyn Material>,
+    ) -> Self {
+        let [a, b, c] = positions.map(|idx| buffers.positions[idx]);
+        let bbox = Aabb::enclosing(&Aabb::from_points(&a, &b), &Aabb::from_points(&c, &c));
+
+        Self {
+            buffers,
+            positions,
+            normals,
+            uvs,
+            mat,
+            bbox: bbox.pad_to_minimums(),
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    // Möller–Trumbore intersection, solves for t and the barycentric coordinates at once
+    fn hit(
+        &self,
+        r: &Ray,
+        ray_t: Interval,
+        rec: &mut HitRecord,
+        _sampler: &mut dyn Sampler,
+    ) -> bool {
+        let [v0, v1, v2] = self.positions.map(|idx| self.buffers.positions[idx]);
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+
+        let pvec = Vec3::cross(r.direction(), &edge2);
+        let det = Vec3::dot(&edge1, &pvec);
+
+        // No hit if the ray is parallel to the triangle plane
+        if f64::abs(det) < 1e-12 {
+            return false;
+        }
+        let inv_det = 1.0 / det;
+
+        let tvec = *r.origin() - v0;
+        let u = Vec3::dot(&tvec, &pvec) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return false;
+        }
+
+        let qvec = Vec3::cross(&tvec, &edge1);
+        let v = Vec3::dot(r.direction(), &qvec) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return false;
+        }
+
+        let t = Vec3::dot(&edge2, &qvec) * inv_det;
+        if !ray_t.surrounds(t) {
+            return false;
+        }
+
+        rec.t = t;
+        rec.p = r.at(t);
+        rec.mat = Some(self.mat.clone());
+
+        // Front face is decided by the geometric normal, the shading normal follows it
+        let geometric_normal = Vec3::unit_vector(&Vec3::cross(&edge1, &edge2));
+        rec.set_face_normal(r, &geometric_normal);
+
+        let w = 1.0 - u - v;
+        if let Some(normals) = self.normals {
+            let [n0, n1, n2] = normals.map(|idx| self.buffers.normals[idx]);
+            let shading_normal = Vec3::unit_vector(&(w * n0 + u * n1 + v * n2));
+            rec.normal = if rec.front_face {
+                shading_normal
+            } else {
+                -shading_normal
+            };
+        }
+
+        // Without texture coordinates the barycentric ones are used
+        (rec.u, rec.v) = match self.uvs {
+            Some(uvs) => {
+                let [t0, t1, t2] = uvs.map(|idx| self.buffers.uvs[idx]);
+                (
+                    w * t0.0 + u * t1.0 + v * t2.0,
+                    w * t0.1 + u * t1.1 + v * t2.1,
+                )
+            }
+            None => (u, v),
+        };
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+
+    fn materials(&self, visit: &mut dyn FnMut(&Arc<dyn Material>)) {
+        visit(&self.mat);
+    }
+
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3, time: f64) -> f64 {
+        let mut rec = HitRecord::default();
+        if !self.hit(
+            &Ray::with_time(*origin, *direction, time),
+            Interval::new(0.001, f64::INFINITY),
+            &mut rec,
+            &mut IndependentSampler,
+        ) {
+            return 0.0;
+        }
+
+        // Uniform over the area, converted to a density over solid angle. The geometric normal
+        // is needed here, the one in the record may be a shading normal
+        let [v0, v1, v2] = self.positions.map(|idx| self.buffers.positions[idx]);
+        let n = Vec3::cross(&(v1 - v0), &(v2 - v0));
+        let area = 0.5 * n.length();
+
+        let distance_squared = rec.t * rec.t * direction.length_squared();
+        let cosine = f64::abs(Vec3::dot(direction, &n) / (direction.length() * n.length()));
+
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: &Point3, _time: f64, u: (f64, f64)) -> Vec3 {
+        let [v0, v1, v2] = self.positions.map(|idx| self.buffers.positions[idx]);
+
+        // Folding the square in half keeps the points uniform over the triangle
+        let (mut a, mut b) = u;
+        if a + b > 1.0 {
+            (a, b) = (1.0 - a, 1.0 - b);
+        }
+
+        v0 + a * (v1 - v0) + b * (v2 - v0) - *origin
+    }
+}
@@ -0,0 +1,155 @@
+use crate::vec3::{Color, mat3::Mat3};
+
+// How scene radiance is squeezed into the 0 to 1 range of a display
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ToneMap {
+    // Anything above 1 is cut off, which keeps the image exactly as rendered below that
+    #[default]
+    Clamp,
+    Reinhard,         // L / (1 + L) on the luminance, never quite reaches white
+    ReinhardExtended, // Reinhard stretched so the white point maps to exactly 1
+    Aces,             // Narkowicz's fit of the ACES filmic curve, contrasty and saturated
+    Agx,              // Minimal AgX, desaturates highlights towards white like film does
+}
+
+impl ToneMap {
+    pub const NAMES: [&str; 5] = ["clamp", "reinhard", "reinhard-extended", "aces", "agx"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "clamp" | "linear" => Some(ToneMap::Clamp),
+            "reinhard" => Some(ToneMap::Reinhard),
+            "reinhard-extended" => Some(ToneMap::ReinhardExtended),
+            "aces" => Some(ToneMap::Aces),
+            "agx" => Some(ToneMap::Agx),
+            _ => None,
+        }
+    }
+}
+
+// Display transform applied before an image is quantized to 8 bits. Formats that store the
+// radiance as is, such as HDR, skip it.
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayTransform {
+    pub exposure: f64, // In stops, every +1 doubles the radiance before tone mapping
+    pub tone_map: ToneMap,
+    pub white_point: f64, // Smallest radiance that turns fully white with extended Reinhard
+}
+
+impl Default for DisplayTransform {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            tone_map: ToneMap::default(),
+            white_point: 4.0,
+        }
+    }
+}
+
+impl DisplayTransform {
+    // Linear radiance to sRGB encoded values, in [0, 1] unless the tone map lets them past
+    pub fn apply(&self, color: Color) -> Color {
+        let color = color * f64::powf(2.0, self.exposure);
+        let mapped = self.tone_map(clamp_negative(color));
+        let mapped = clamp_negative(mapped);
+
+        Color::new(
+            linear_to_srgb(mapped.x()),
+            linear_to_srgb(mapped.y()),
+            linear_to_srgb(mapped.z()),
+        )
+    }
+
+    fn tone_map(&self, color: Color) -> Color {
+        match self.tone_map {
+            ToneMap::Clamp => color,
+            ToneMap::Reinhard => {
+                let luminance = color.luminance();
+                color / (1.0 + luminance)
+            }
+            ToneMap::ReinhardExtended => {
+                let luminance = color.luminance();
+                if luminance <= 0.0 {
+                    return color;
+                }
+                let white = self.white_point * self.white_point;
+                let mapped = luminance * (1.0 + luminance / white) / (1.0 + luminance);
+                color * (mapped / luminance)
+            }
+            ToneMap::Aces => {
+                // The fit expects a brighter input than the ACES reference, scale it back down
+                let aces = |x: f64| {
+                    let x = 0.6 * x;
+                    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+                };
+                Color::new(aces(color.x()), aces(color.y()), aces(color.z()))
+            }
+            ToneMap::Agx => agx(color),
+        }
+    }
+}
+
+// Piecewise sRGB transfer function, linear near black and a 2.4 power above
+pub fn linear_to_srgb(linear: f64) -> f64 {
+    if linear <= 0.0031308 {
+        return 12.92 * linear;
+    }
+    1.055 * linear.powf(1.0 / 2.4) - 0.055
+}
+
+// Inverse of linear_to_srgb, for 8 bit images read back as textures
+pub fn srgb_to_linear(encoded: f64) -> f64 {
+    if encoded <= 0.04045 {
+        return encoded / 12.92;
+    }
+    ((encoded + 0.055) / 1.055).powf(2.4)
+}
+
+fn clamp_negative(color: Color) -> Color {
+    Color::new(color.x().max(0.0), color.y().max(0.0), color.z().max(0.0))
+}
+
+// Rec. 709 into the slightly inset primaries AgX is defined in, and back out
+const AGX_INSET: Mat3 = Mat3 {
+    m: [
+        [0.842479062253094, 0.0784335999999992, 0.0792237451477643],
+        [0.0423282422610123, 0.878468636469772, 0.0791661274605434],
+        [0.0423756549057051, 0.0784336, 0.879142973793104],
+    ],
+};
+const AGX_OUTSET: Mat3 = Mat3 {
+    m: [
+        [1.19687900512017, -0.0980208811401368, -0.0990297440797205],
+        [-0.0528968517574562, 1.15190312990417, -0.0989611768448433],
+        [-0.0529716355144438, -0.0980434501171241, 1.15107367264116],
+    ],
+};
+
+// Range of stops around middle grey the curve covers
+const AGX_MIN_EV: f64 = -12.47393;
+const AGX_MAX_EV: f64 = 4.026069;
+
+fn agx(color: Color) -> Color {
+    let inset = AGX_INSET * color;
+
+    // Log encode, then push every channel through the same sigmoid
+    let curve = |x: f64| {
+        let x = f64::log2(x.max(1e-10)).clamp(AGX_MIN_EV, AGX_MAX_EV);
+        let x = (x - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+
+        // Polynomial fit of the default AgX contrast curve
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232
+    };
+    let encoded = AGX_OUTSET * Color::new(curve(inset.x()), curve(inset.y()), curve(inset.z()));
+
+    // The curve targets a 2.2 gamma display, back to linear so the sRGB encoding applies after
+    let linear = |x: f64| x.max(0.0).powf(2.2);
+    Color::new(
+        linear(encoded.x()),
+        linear(encoded.y()),
+        linear(encoded.z()),
+    )
+}
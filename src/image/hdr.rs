@@ -0,0 +1,383 @@
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::vec3::Color;
+
+use super::{FromFile, Image, ToFile};
+
+// Shortest run worth encoding as a run in the RLE scanlines
+const MIN_RUN: usize = 4;
+
+// Radiance RGBE (.hdr) image, stores the unclamped linear radiance with a shared exponent
+#[allow(clippy::upper_case_acronyms)]
+pub struct HDR {
+    data: Vec<[u8; 4]>,
+    width: u32,
+    height: u32,
+}
+
+impl From<Image> for HDR {
+    fn from(value: Image) -> Self {
+        let converted = value
+            .data
+            .iter()
+            .map(|x| HDR::to_rgbe(x.red, x.green, x.blue))
+            .collect();
+
+        HDR {
+            data: converted,
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl HDR {
+    // Packs three floats into mantissas sharing the exponent of the brightest component
+    fn to_rgbe(red: f64, green: f64, blue: f64) -> [u8; 4] {
+        // Radiance can not be negative, and RGBE has no way to store it anyway
+        let (red, green, blue) = (red.max(0.0), green.max(0.0), blue.max(0.0));
+        let v = red.max(green).max(blue);
+        if !v.is_finite() || v < 1e-32 {
+            return [0, 0, 0, 0];
+        }
+
+        // v = mantissa * 2^exponent with mantissa in [0.5, 1)
+        let mut exponent = v.log2().floor() as i32 + 1;
+        if v / 2f64.powi(exponent) >= 1.0 {
+            exponent += 1;
+        } else if v / 2f64.powi(exponent) < 0.5 {
+            exponent -= 1;
+        }
+        let exponent = exponent.clamp(-128, 127);
+
+        let scale = 256.0 / 2f64.powi(exponent);
+        [
+            (red * scale).min(255.0) as u8,
+            (green * scale).min(255.0) as u8,
+            (blue * scale).min(255.0) as u8,
+            (exponent + 128) as u8,
+        ]
+    }
+
+    fn from_rgbe(rgbe: [u8; 4]) -> Color {
+        if rgbe[3] == 0 {
+            return Color::default();
+        }
+
+        // Values are reconstructed at the center of their quantization step
+        let f = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+        Color::new(
+            (rgbe[0] as f64 + 0.5) * f,
+            (rgbe[1] as f64 + 0.5) * f,
+            (rgbe[2] as f64 + 0.5) * f,
+        )
+    }
+
+    // Adaptive run length encoding only works for these widths, otherwise scanlines are flat
+    fn uses_rle(width: u32) -> bool {
+        (8..0x8000).contains(&width)
+    }
+
+    // Encodes one channel of a scanline as a mix of runs and literal dumps
+    fn write_rle_channel(out: &mut Vec<u8>, values: &[u8]) {
+        let mut cur = 0;
+
+        while cur < values.len() {
+            // Find the start of the next run that is long enough to be worth it
+            let mut beg_run = cur;
+            let mut run_count = 0;
+            let mut old_run_count = 0;
+            while run_count < MIN_RUN && beg_run < values.len() {
+                beg_run += run_count;
+                old_run_count = run_count;
+                run_count = 1;
+                while beg_run + run_count < values.len()
+                    && run_count < 127
+                    && values[beg_run] == values[beg_run + run_count]
+                {
+                    run_count += 1;
+                }
+            }
+
+            // A short run right at the start is still cheaper than a literal dump
+            if old_run_count > 1 && old_run_count == beg_run - cur {
+                out.push(128 + old_run_count as u8);
+                out.push(values[cur]);
+                cur = beg_run;
+            }
+
+            // Everything up to the run goes out as literals, at most 128 at a time
+            while cur < beg_run {
+                let count = usize::min(128, beg_run - cur);
+                out.push(count as u8);
+                out.extend_from_slice(&values[cur..cur + count]);
+                cur += count;
+            }
+
+            if run_count >= MIN_RUN {
+                out.push(128 + run_count as u8);
+                out.push(values[beg_run]);
+                cur += run_count;
+            }
+        }
+    }
+
+    fn encode_scanline(&self, row: &[[u8; 4]], out: &mut Vec<u8>) {
+        if !Self::uses_rle(self.width) {
+            row.iter().for_each(|rgbe| out.extend_from_slice(rgbe));
+            return;
+        }
+
+        out.extend_from_slice(&[2, 2, (self.width >> 8) as u8, (self.width & 0xFF) as u8]);
+        for channel in 0..4 {
+            let values: Vec<u8> = row.iter().map(|rgbe| rgbe[channel]).collect();
+            Self::write_rle_channel(out, &values);
+        }
+    }
+
+    fn decode_scanline(bytes: &[u8], pos: &mut usize, width: usize) -> Result<Vec<[u8; 4]>> {
+        let mut take = |count: usize| -> Result<&[u8]> {
+            let slice = bytes
+                .get(*pos..*pos + count)
+                .ok_or_else(|| anyhow!("Unexpected end of pixel data."))?;
+            *pos += count;
+            Ok(slice)
+        };
+
+        let start = take(4)?;
+        let is_rle =
+            Self::uses_rle(width as u32) && start[0] == 2 && start[1] == 2 && start[2] & 0x80 == 0;
+
+        if !is_rle {
+            // Flat scanline, the four bytes we just read are the first pixel
+            let mut row = vec![[start[0], start[1], start[2], start[3]]];
+            for _ in 1..width {
+                let p = take(4)?;
+                row.push([p[0], p[1], p[2], p[3]]);
+            }
+            return Ok(row);
+        }
+
+        if ((start[2] as usize) << 8 | start[3] as usize) != width {
+            bail!("Scanline width does not match the image width.");
+        }
+
+        let mut row = vec![[0u8; 4]; width];
+        for channel in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = take(1)?[0] as usize;
+                if count > 128 {
+                    // Run of a single value
+                    let count = count - 128;
+                    let value = take(1)?[0];
+                    if x + count > width {
+                        bail!("Run length overflows the scanline.");
+                    }
+                    row[x..x + count]
+                        .iter_mut()
+                        .for_each(|p| p[channel] = value);
+                    x += count;
+                } else {
+                    // Literal dump
+                    if count == 0 || x + count > width {
+                        bail!("Invalid literal length in scanline.");
+                    }
+                    for (p, value) in row[x..x + count].iter_mut().zip(take(count)?) {
+                        p[channel] = *value;
+                    }
+                    x += count;
+                }
+            }
+        }
+
+        Ok(row)
+    }
+}
+
+impl ToFile for HDR {
+    fn get_metadata(&self) -> String {
+        format!(
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        )
+    }
+
+    fn save(&self, image_path: &str) -> Result<()> {
+        HDR::delete_if_exits(image_path)?;
+
+        match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(image_path)
+        {
+            Ok(open_file) => {
+                let mut out = BufWriter::new(open_file);
+                out.write_all(self.get_metadata().as_bytes())?;
+
+                let mut encoded = vec![];
+                for row in self.data.chunks(self.width as usize) {
+                    encoded.clear();
+                    self.encode_scanline(row, &mut encoded);
+                    out.write_all(&encoded)?;
+                }
+                out.flush()?;
+                Ok(())
+            }
+
+            Err(_) => Err(anyhow!("Could not create new file for image saving.")),
+        }
+    }
+}
+
+impl FromFile for HDR {
+    fn load(image_path: &str) -> Result<Image> {
+        let bytes = std::fs::read(image_path)
+            .with_context(|| format!("Could not read image {}", image_path))?;
+        let mut pos = 0;
+
+        let mut next_line = || -> Result<&str> {
+            let len = bytes[pos..]
+                .iter()
+                .position(|b| *b == b'\n')
+                .ok_or_else(|| anyhow!("Unexpected end of header."))?;
+            let line = std::str::from_utf8(&bytes[pos..pos + len])?;
+            pos += len + 1;
+            Ok(line.trim_end_matches('\r'))
+        };
+
+        let magic = next_line()?;
+        if magic != "#?RADIANCE" && magic != "#?RGBE" {
+            bail!("Not a Radiance HDR file.");
+        }
+
+        // Header variables run until the first empty line
+        loop {
+            let line = next_line()?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some(format) = line.strip_prefix("FORMAT=")
+                && format != "32-bit_rle_rgbe"
+            {
+                bail!("Unsupported HDR pixel format {}.", format);
+            }
+        }
+
+        // Only the standard top to bottom, left to right orientation is supported
+        let resolution: Vec<&str> = next_line()?.split_whitespace().collect();
+        let (height, width) = match resolution.as_slice() {
+            ["-Y", height, "+X", width] => (height.parse::<u32>()?, width.parse::<u32>()?),
+            _ => bail!(
+                "Unsupported HDR resolution line {:?}.",
+                resolution.join(" ")
+            ),
+        };
+
+        if width == 0 || height == 0 {
+            bail!("Invalid HDR size {}x{}.", width, height);
+        }
+
+        // The size comes straight from the file, only reserve what the rest of it can hold
+        let pixel_count = (width as usize)
+            .checked_mul(height as usize)
+            .ok_or_else(|| anyhow!("Invalid HDR size {}x{}.", width, height))?;
+        let mut data = Vec::with_capacity(pixel_count.min(bytes.len() - pos));
+        for _ in 0..height {
+            let row = HDR::decode_scanline(&bytes, &mut pos, width as usize)?;
+            data.extend(row.into_iter().map(HDR::from_rgbe));
+        }
+
+        let mut image = Image::new(width, height);
+        image.load_data(data);
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::image::{
+        Format,
+        testing::{assert_close, gradient, temp_file, temp_path},
+    };
+
+    use super::*;
+
+    // RGBE keeps 8 bits of mantissa, relative to the brightest channel of the pixel
+    fn round_trip(width: u32, height: u32) -> u64 {
+        let path = temp_path(&format!("round_trip_{}.hdr", width));
+        gradient(width, height, 4.0)
+            .save(&path, Format::Hdr, false)
+            .unwrap();
+        let size = fs::metadata(&path).unwrap().len();
+        let loaded = HDR::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_close(&gradient(width, height, 4.0), &loaded, 4.0 / 256.0, |c| c);
+        size
+    }
+
+    #[test]
+    fn run_length_encoded_round_trip() {
+        let (width, height) = (40, 6);
+        let header = "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 6 +X 40\n".len() as u64;
+        let size = round_trip(width, height);
+        assert!(size < header + (width * height * 4) as u64);
+    }
+
+    #[test]
+    fn flat_round_trip() {
+        // Too narrow for run length encoding
+        round_trip(5, 3);
+    }
+
+    #[test]
+    fn rgbe_keeps_black_and_clamps_negative() {
+        assert_eq!(HDR::to_rgbe(0.0, -1.0, 0.0), [0, 0, 0, 0]);
+        assert_eq!(HDR::from_rgbe([0, 0, 0, 0]).e, [0.0; 3]);
+
+        let color = HDR::from_rgbe(HDR::to_rgbe(1.0, 0.5, 0.0));
+        assert!((color.x() - 1.0).abs() <= 1.0 / 256.0);
+        assert!((color.y() - 0.5).abs() <= 1.0 / 256.0);
+        assert!(color.z() <= 1.0 / 256.0);
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let cases: [(&str, &[u8], &str); 4] = [
+            (
+                "zero.hdr",
+                b"#?RADIANCE\n\n-Y 4 +X 0\nabcdabcd",
+                "Invalid HDR size 0x4.",
+            ),
+            (
+                "huge.hdr",
+                b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 65535 +X 65535\n",
+                "Unexpected end of pixel data.",
+            ),
+            (
+                "flipped.hdr",
+                b"#?RADIANCE\n\n+Y 1 +X 1\n\0\0\0\0",
+                "Unsupported HDR resolution line \"+Y 1 +X 1\".",
+            ),
+            ("magic.hdr", b"P6\n1 1\n255\n", "Not a Radiance HDR file."),
+        ];
+        for (name, bytes, message) in cases {
+            let path = temp_file(name, bytes);
+            let result = HDR::load(&path);
+            fs::remove_file(&path).unwrap();
+            assert_eq!(
+                result.err().map(|e| e.to_string()).as_deref(),
+                Some(message)
+            );
+        }
+    }
+}
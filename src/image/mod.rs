@@ -1,25 +1,154 @@
 use std::path::Path;
 
 use anyhow::{Result, anyhow};
-use pixel::fPixel;
+use display::DisplayTransform;
+use hdr::HDR;
+use pfm::PFM;
+use pixel::{fPixel, uPixel};
+use png::PNG;
+use ppm::PPM;
 
-use crate::vec3::Color;
+use crate::{utility::interval::Interval, vec3::Color};
 
+pub mod display;
+pub mod hdr;
+pub mod pfm;
 pub mod pixel;
+pub mod png;
 pub mod ppm;
 
 pub struct Image {
     data: Vec<fPixel>,
+    weights: Vec<f64>, // Filter weight sums of a film, empty for a finished image
     width: u32,
     height: u32,
+    display: DisplayTransform, // Applied when writing 8 bit formats
 }
 
 impl Image {
     pub fn new(width: u32, height: u32) -> Self {
         Self {
             data: vec![],
+            weights: vec![],
             width,
             height,
+            display: DisplayTransform::default(),
+        }
+    }
+
+    // Black film that samples can be splatted into, with a weight sum per pixel
+    pub fn film(width: u32, height: u32) -> Self {
+        let size = (width * height) as usize;
+        let mut data = Vec::with_capacity(size);
+        data.resize_with(size, fPixel::default);
+
+        Self {
+            data,
+            weights: vec![0.0; size],
+            width,
+            height,
+            display: DisplayTransform::default(),
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Linear color of the pixel in column x and row y, counted from the top left
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let p = &self.data[(y * self.width + x) as usize];
+        Color::new(p.red, p.green, p.blue)
+    }
+
+    // Alpha channel taken from the red channel of another image of the same size, such as the
+    // coverage pass. Without one every pixel is opaque
+    pub fn with_alpha(mut self, alpha: &Image) -> Self {
+        for (pixel, source) in self.data.iter_mut().zip(&alpha.data) {
+            pixel.alpha = source.red.clamp(0.0, 1.0);
+        }
+        self
+    }
+
+    pub fn with_display(mut self, display: DisplayTransform) -> Self {
+        self.display = display;
+        self
+    }
+
+    // Filter weight summed up in the pixel of a film so far
+    pub fn weight(&self, x: u32, y: u32) -> f64 {
+        self.weights[(y * self.width + x) as usize]
+    }
+
+    // Adds a sample to a film pixel, the color should already be multiplied by the weight
+    pub fn splat(&mut self, x: u32, y: u32, color: Color, weight: f64) {
+        let index = (y * self.width + x) as usize;
+        let p = &mut self.data[index];
+        p.red += color.e[0];
+        p.green += color.e[1];
+        p.blue += color.e[2];
+        self.weights[index] += weight;
+    }
+
+    // Adds another film of the same width on top of this one, its first row landing on row
+    // `top`. Rows that fall outside of this film are dropped.
+    pub fn accumulate(&mut self, other: &Image, top: i64) {
+        for y in 0..other.height {
+            let Ok(row) = u32::try_from(top + y as i64) else {
+                continue;
+            };
+            if row >= self.height {
+                break;
+            }
+
+            for x in 0..self.width {
+                let color = other.pixel(x, y);
+                self.splat(x, row, color, other.weight(x, y));
+            }
+        }
+    }
+
+    // Finished image of a film, every pixel divided by its weight sum. Pixels nothing landed in
+    // stay black, and negative lobes of the filter can not make a pixel darker than black.
+    pub fn resolve(&self) -> Image {
+        let data = self
+            .data
+            .iter()
+            .zip(&self.weights)
+            .map(|(p, &weight)| {
+                if weight == 0.0 {
+                    return Color::default();
+                }
+                let color = Color::new(p.red, p.green, p.blue) / weight;
+                Color::new(color.x().max(0.0), color.y().max(0.0), color.z().max(0.0))
+            })
+            .collect();
+
+        let mut image = Image::new(self.width, self.height);
+        image.load_data(data);
+        image
+    }
+
+    // Reads any format we have a reader for, picked by the file extension
+    pub fn load(image_path: &str) -> Result<Image> {
+        let extension = Path::new(image_path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match extension.as_str() {
+            "ppm" | "pgm" | "pnm" => PPM::load(image_path),
+            "hdr" => HDR::load(image_path),
+            "pfm" => PFM::load(image_path),
+            _ => Err(anyhow!(
+                "Can not read {}, only PPM, PGM, HDR and PFM images are supported.",
+                image_path
+            )),
         }
     }
 
@@ -27,13 +156,89 @@ impl Image {
         self.data = data
             .iter()
             .map(|value| fPixel {
-                red: value.e[0] as f64,
-                green: value.e[1] as f64,
-                blue: value.e[2] as f64,
-                alpha: 0.0,
+                red: value.e[0],
+                green: value.e[1],
+                blue: value.e[2],
+                alpha: 1.0,
             })
             .collect()
     }
+
+    // Converts the linear radiance into display encoded 8 bit values, shared by all LDR writers
+    fn quantize(&self) -> Vec<uPixel> {
+        // The display transform brings pixels to values from 0.0 to 1.0
+        // 8 bit formats require us to use values from 0 to 255
+        self.data
+            .iter()
+            .map(|x| {
+                let encoded = self.display.apply(Color::new(x.red, x.green, x.blue));
+                let alpha = x.alpha;
+
+                uPixel {
+                    red: (256.0 * INTENSITY.clamp(encoded.x())) as u8,
+                    green: (256.0 * INTENSITY.clamp(encoded.y())) as u8,
+                    blue: (256.0 * INTENSITY.clamp(encoded.z())) as u8,
+                    alpha: (256.0 * INTENSITY.clamp(alpha)) as u8,
+                }
+            })
+            .collect()
+    }
+}
+
+const INTENSITY: Interval = Interval {
+    min: 0.000,
+    max: 0.999,
+};
+
+// File formats an image can be written as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Ppm { binary: bool },
+    Png,
+    Hdr,
+    Pfm,
+}
+
+impl Format {
+    pub const NAMES: [&str; 5] = ["ppm", "ppm-binary", "png", "hdr", "pfm"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ppm" => Some(Format::Ppm { binary: false }),
+            "ppm-binary" => Some(Format::Ppm { binary: true }),
+            "png" => Some(Format::Png),
+            "hdr" => Some(Format::Hdr),
+            "pfm" => Some(Format::Pfm),
+            _ => None,
+        }
+    }
+
+    // Guess the format from the file extension
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = Path::new(path).extension()?.to_str()?;
+        Self::from_name(extension)
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Ppm { .. } => "ppm",
+            Format::Png => "png",
+            Format::Hdr => "hdr",
+            Format::Pfm => "pfm",
+        }
+    }
+}
+
+impl Image {
+    // Alpha is only written by formats that can store it
+    pub fn save(self, image_path: &str, format: Format, alpha: bool) -> Result<()> {
+        match format {
+            Format::Ppm { binary } => PPM::from(self).with_binary(binary).save(image_path),
+            Format::Png => PNG::from(self).with_alpha(alpha).save(image_path),
+            Format::Hdr => HDR::from(self).save(image_path),
+            Format::Pfm => PFM::from(self).save(image_path),
+        }
+    }
 }
 
 pub trait ToFile {
@@ -52,3 +257,81 @@ pub trait ToFile {
         Ok(())
     }
 }
+
+pub trait FromFile {
+    fn load(image_path: &str) -> Result<Image>;
+}
+
+// Images and files shared by the tests of the readers and writers
+#[cfg(test)]
+pub(super) mod testing {
+    use std::{env, fs, process};
+
+    use crate::vec3::Color;
+
+    use super::Image;
+
+    // Smooth ramps from 0 to `scale` with every other row flat, so run length encoders see
+    // both runs and literals
+    pub fn gradient(width: u32, height: u32, scale: f64) -> Image {
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                if y % 2 == 1 {
+                    return Color::new(0.25, 0.5, 0.75) * scale;
+                }
+                let u = x as f64 / width as f64;
+                let v = y as f64 / height as f64;
+                Color::new(u, v, (u + v) / 2.0) * scale
+            })
+            .collect();
+
+        let mut image = Image::new(width, height);
+        image.load_data(data);
+        image
+    }
+
+    // Path in the temp directory, unique to the test process and `name`
+    pub fn temp_path(name: &str) -> String {
+        env::temp_dir()
+            .join(format!("ray-tracing-{}-{}", process::id(), name))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    pub fn temp_file(name: &str, bytes: &[u8]) -> String {
+        let path = temp_path(name);
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    // Every pixel of `loaded` within `tolerance` of the pixel of `original`, after `encode`
+    pub fn assert_close(
+        original: &Image,
+        loaded: &Image,
+        tolerance: f64,
+        encode: impl Fn(f64) -> f64,
+    ) {
+        assert_eq!(
+            (loaded.width(), loaded.height()),
+            (original.width(), original.height())
+        );
+        for y in 0..original.height() {
+            for x in 0..original.width() {
+                let (a, b) = (original.pixel(x, y), loaded.pixel(x, y));
+                for channel in 0..3 {
+                    let error = (encode(a.e[channel]) - encode(b.e[channel])).abs();
+                    assert!(
+                        error <= tolerance,
+                        "pixel {} {} channel {}: {} became {}",
+                        x,
+                        y,
+                        channel,
+                        a.e[channel],
+                        b.e[channel]
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::vec3::Color;
+
+use super::{FromFile, Image, ToFile};
+
+// Portable float map, plain 32 bit floats per channel. Unlike RGBE it keeps negative values,
+// which normals and positions need
+#[allow(clippy::upper_case_acronyms)]
+pub struct PFM {
+    data: Vec<[f32; 3]>,
+    width: u32,
+    height: u32,
+}
+
+impl From<Image> for PFM {
+    fn from(value: Image) -> Self {
+        let converted = value
+            .data
+            .iter()
+            .map(|x| [x.red as f32, x.green as f32, x.blue as f32])
+            .collect();
+
+        PFM {
+            data: converted,
+            width: value.width,
+            height: value.height,
+        }
+    }
+}
+
+impl ToFile for PFM {
+    // A negative scale marks the floats as little endian
+    fn get_metadata(&self) -> String {
+        format!("PF\n{} {}\n-1.0\n", self.width, self.height)
+    }
+
+    fn save(&self, image_path: &str) -> Result<()> {
+        PFM::delete_if_exits(image_path)?;
+
+        match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(image_path)
+        {
+            Ok(open_file) => {
+                let mut out = BufWriter::new(open_file);
+                out.write_all(self.get_metadata().as_bytes())?;
+
+                // Rows are stored from the bottom of the image up
+                for row in self.data.chunks(self.width as usize).rev() {
+                    for pixel in row {
+                        for channel in pixel {
+                            out.write_all(&channel.to_le_bytes())?;
+                        }
+                    }
+                }
+                out.flush()?;
+                Ok(())
+            }
+
+            Err(_) => Err(anyhow!("Could not create new file for image saving.")),
+        }
+    }
+}
+
+// Reads color (PF) and grayscale (Pf) maps of either byte order
+impl FromFile for PFM {
+    fn load(image_path: &str) -> Result<Image> {
+        let bytes = std::fs::read(image_path)
+            .with_context(|| format!("Could not read image {}", image_path))?;
+
+        // Three header lines: the magic, the size and the scale whose sign is the byte order
+        let mut lines = bytes.splitn(4, |b| *b == b'\n');
+        let mut next_line = || -> Result<&str> {
+            let line = lines
+                .next()
+                .ok_or_else(|| anyhow!("Unexpected end of header."))?;
+            Ok(std::str::from_utf8(line)?.trim())
+        };
+
+        let channels = match next_line()? {
+            "PF" => 3,
+            "Pf" => 1,
+            _ => bail!("Not a PFM file."),
+        };
+        let size: Vec<u32> = next_line()?
+            .split_whitespace()
+            .map(|n| n.parse())
+            .collect::<Result<_, _>>()?;
+        let [width, height] = size[..] else {
+            bail!("Invalid PFM size line.");
+        };
+        if width == 0 || height == 0 {
+            bail!("Invalid PFM size {}x{}.", width, height);
+        }
+        let little_endian = next_line()?.parse::<f32>()? < 0.0;
+
+        let raster = lines.next().unwrap_or_default();
+        let sample_count = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .filter(|samples| {
+                samples
+                    .checked_mul(4)
+                    .is_some_and(|size| raster.len() >= size)
+            })
+            .ok_or_else(|| anyhow!("Unexpected end of pixel data."))?;
+
+        let samples: Vec<f64> = raster
+            .chunks_exact(4)
+            .take(sample_count)
+            .map(|b| {
+                let b = [b[0], b[1], b[2], b[3]];
+                if little_endian {
+                    f32::from_le_bytes(b) as f64
+                } else {
+                    f32::from_be_bytes(b) as f64
+                }
+            })
+            .collect();
+
+        // Back to top to bottom rows
+        let data = samples
+            .chunks_exact(width as usize * channels)
+            .rev()
+            .flat_map(|row| row.chunks_exact(channels))
+            .map(|pixel| match pixel {
+                [gray] => Color::new(*gray, *gray, *gray),
+                _ => Color::new(pixel[0], pixel[1], pixel[2]),
+            })
+            .collect();
+
+        let mut image = Image::new(width, height);
+        image.load_data(data);
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::image::{
+        Format,
+        testing::{assert_close, gradient, temp_file, temp_path},
+    };
+
+    use super::*;
+
+    #[test]
+    fn round_trip_keeps_the_floats() {
+        // Negative and above one, which the 8 bit formats can not keep
+        let path = temp_path("round_trip.pfm");
+        gradient(7, 5, -3.0)
+            .save(&path, Format::Pfm, false)
+            .unwrap();
+        let loaded = PFM::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_close(&gradient(7, 5, -3.0), &loaded, 0.0, |c| c as f32 as f64);
+    }
+
+    #[test]
+    fn reads_big_endian_grayscale() {
+        // Bottom row first
+        let mut bytes = b"Pf\n1 2\n1.0\n".to_vec();
+        bytes.extend_from_slice(&0.5f32.to_be_bytes());
+        bytes.extend_from_slice(&2.0f32.to_be_bytes());
+        let path = temp_file("gray.pfm", &bytes);
+        let image = PFM::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(image.pixel(0, 0).e, [2.0; 3]);
+        assert_eq!(image.pixel(0, 1).e, [0.5; 3]);
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let cases: [(&str, &[u8], &str); 4] = [
+            ("zero.pfm", b"PF\n0 4\n-1.0\n", "Invalid PFM size 0x4."),
+            (
+                "huge.pfm",
+                b"PF\n4294967295 4294967295\n-1.0\n\0\0\0\0",
+                "Unexpected end of pixel data.",
+            ),
+            ("size.pfm", b"PF\n4\n-1.0\n", "Invalid PFM size line."),
+            ("magic.pfm", b"P6\n1 1\n255\n\0\0\0", "Not a PFM file."),
+        ];
+        for (name, bytes, message) in cases {
+            let path = temp_file(name, bytes);
+            let result = PFM::load(&path);
+            fs::remove_file(&path).unwrap();
+            assert_eq!(
+                result.err().map(|e| e.to_string()).as_deref(),
+                Some(message)
+            );
+        }
+    }
+}
@@ -1,3 +1,4 @@
+#[allow(non_camel_case_types)]
 #[derive(Default, Debug)]
 pub struct fPixel {
     pub red: f64,
@@ -6,6 +7,7 @@ pub struct fPixel {
     pub alpha: f64,
 }
 
+#[allow(non_camel_case_types)]
 #[derive(Default, Debug)]
 pub struct uPixel {
     pub red: u8,
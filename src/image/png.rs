@@ -0,0 +1,318 @@
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
+
+use anyhow::{Result, anyhow};
+
+use super::{Image, ToFile, pixel::uPixel};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+// Largest payload a single stored deflate block can carry
+const MAX_STORED_BLOCK: usize = 0xFFFF;
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct PNG {
+    data: Vec<uPixel>,
+    width: u32,
+    height: u32,
+    alpha: bool,
+}
+
+impl From<Image> for PNG {
+    fn from(value: Image) -> Self {
+        PNG {
+            data: value.quantize(),
+            width: value.width,
+            height: value.height,
+            alpha: false,
+        }
+    }
+}
+
+impl PNG {
+    // Write RGBA instead of RGB
+    pub fn with_alpha(mut self, alpha: bool) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    fn channels(&self) -> usize {
+        if self.alpha { 4 } else { 3 }
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let mut ihdr = vec![];
+        ihdr.extend_from_slice(&self.width.to_be_bytes());
+        ihdr.extend_from_slice(&self.height.to_be_bytes());
+        ihdr.push(8); // Bit depth
+        ihdr.push(if self.alpha { 6 } else { 2 }); // Color type, truecolor with or without alpha
+        ihdr.push(0); // Compression method, deflate
+        ihdr.push(0); // Filter method, adaptive
+        ihdr.push(0); // No interlacing
+        ihdr
+    }
+
+    // Raw scanlines, each prefixed with its filter type
+    fn scanlines(&self) -> Vec<u8> {
+        let row_size = 1 + self.width as usize * self.channels();
+        let mut raw = Vec::with_capacity(row_size * self.height as usize);
+
+        for row in self.data.chunks(self.width as usize) {
+            raw.push(0); // Filter type None
+            for upixel in row {
+                raw.extend_from_slice(&[upixel.red, upixel.green, upixel.blue]);
+                if self.alpha {
+                    raw.push(upixel.alpha);
+                }
+            }
+        }
+
+        raw
+    }
+
+    fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> Result<()> {
+        out.write_all(&(data.len() as u32).to_be_bytes())?;
+        out.write_all(kind)?;
+        out.write_all(data)?;
+
+        // CRC covers the chunk type and data, but not the length
+        let crc = !crc32_update(crc32_update(!0, kind), data);
+        out.write_all(&crc.to_be_bytes())?;
+        Ok(())
+    }
+}
+
+impl ToFile for PNG {
+    fn get_metadata(&self) -> String {
+        format!(
+            "PNG {}x{} 8-bit {}",
+            self.width,
+            self.height,
+            if self.alpha { "RGBA" } else { "RGB" }
+        )
+    }
+
+    fn save(&self, image_path: &str) -> Result<()> {
+        PNG::delete_if_exits(image_path)?;
+
+        match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(image_path)
+        {
+            Ok(open_file) => {
+                let mut out = BufWriter::new(open_file);
+                out.write_all(&SIGNATURE)?;
+                PNG::write_chunk(&mut out, b"IHDR", &self.header())?;
+                PNG::write_chunk(&mut out, b"IDAT", &zlib_stored(&self.scanlines()))?;
+                PNG::write_chunk(&mut out, b"IEND", &[])?;
+                out.flush()?;
+                Ok(())
+            }
+
+            Err(_) => Err(anyhow!("Could not create new file for image saving.")),
+        }
+    }
+}
+
+// Wraps data in a zlib stream made of uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut out = Vec::with_capacity(data.len() + blocks * 5 + 6);
+
+    // CMF: deflate with a 32K window, FLG: no dictionary, check bits make the header divisible by 31
+    out.extend_from_slice(&[0x78, 0x01]);
+
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        // Even empty data needs one final block
+        out.extend_from_slice(&[0x01, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let len = chunk.len() as u16;
+
+        out.push(if last { 0x01 } else { 0x00 }); // BFINAL bit, BTYPE 00 (stored)
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(chunk);
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    // Reducing every 5552 bytes is the most we can do without overflowing u32
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+
+    (b << 16) | a
+}
+
+const CRC_TABLE: [u32; 256] = crc32_table();
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+// Running CRC, start from !0 and invert the final value
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc = CRC_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{
+        image::{
+            Format,
+            display::{linear_to_srgb, srgb_to_linear},
+            testing::{assert_close, gradient, temp_path},
+        },
+        vec3::Color,
+    };
+
+    use super::*;
+
+    // Reads back what `save` writes: checked chunks, stored deflate blocks and unfiltered
+    // scanlines. Returns the image and the alpha of every pixel, if stored
+    fn decode(bytes: &[u8]) -> (Image, Option<Vec<u8>>) {
+        assert_eq!(bytes[..8], SIGNATURE);
+
+        let mut chunks = vec![];
+        let mut pos = 8;
+        while pos < bytes.len() {
+            let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap()) as usize;
+            let kind = &bytes[pos + 4..pos + 8];
+            let data = &bytes[pos + 8..pos + 8 + len];
+            let crc = u32::from_be_bytes(bytes[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc, !crc32_update(crc32_update(!0, kind), data));
+            chunks.push((kind, data));
+            pos += 12 + len;
+        }
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(kinds, [&b"IHDR"[..], b"IDAT", b"IEND"]);
+
+        let ihdr = chunks[0].1;
+        let width = u32::from_be_bytes(ihdr[0..4].try_into().unwrap());
+        let height = u32::from_be_bytes(ihdr[4..8].try_into().unwrap());
+        let channels = match ihdr[8..] {
+            [8, 2, 0, 0, 0] => 3,
+            [8, 6, 0, 0, 0] => 4,
+            _ => panic!("unexpected IHDR {:?}", ihdr),
+        };
+
+        // zlib header, stored blocks until the final one, Adler-32 of the raw data
+        let zlib = chunks[1].1;
+        assert_eq!(zlib[..2], [0x78, 0x01]);
+        let mut raw = vec![];
+        let mut pos = 2;
+        loop {
+            let last = zlib[pos] == 0x01;
+            let len = u16::from_le_bytes([zlib[pos + 1], zlib[pos + 2]]);
+            let nlen = u16::from_le_bytes([zlib[pos + 3], zlib[pos + 4]]);
+            assert_eq!(nlen, !len);
+            raw.extend_from_slice(&zlib[pos + 5..pos + 5 + len as usize]);
+            pos += 5 + len as usize;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(zlib[pos..], adler32(&raw).to_be_bytes());
+
+        let row_size = 1 + width as usize * channels;
+        assert_eq!(raw.len(), row_size * height as usize);
+        let mut data = vec![];
+        let mut alpha = vec![];
+        for row in raw.chunks(row_size) {
+            assert_eq!(row[0], 0);
+            for pixel in row[1..].chunks(channels) {
+                let linear = |b: u8| srgb_to_linear((b as f64 + 0.5) / 256.0);
+                data.push(Color::new(
+                    linear(pixel[0]),
+                    linear(pixel[1]),
+                    linear(pixel[2]),
+                ));
+                alpha.extend(pixel.get(3));
+            }
+        }
+
+        let mut image = Image::new(width, height);
+        image.load_data(data);
+        (image, (channels == 4).then_some(alpha))
+    }
+
+    fn round_trip(name: &str, image: Image, alpha: bool) -> (Image, Option<Vec<u8>>) {
+        let path = temp_path(name);
+        image.save(&path, Format::Png, alpha).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        decode(&bytes)
+    }
+
+    #[test]
+    fn round_trip_within_8_bits() {
+        // Big enough to need several stored blocks
+        let (loaded, alpha) = round_trip("round_trip.png", gradient(160, 150, 0.95), false);
+        assert!(alpha.is_none());
+        assert_close(
+            &gradient(160, 150, 0.95),
+            &loaded,
+            0.5 / 256.0 + 1e-9,
+            linear_to_srgb,
+        );
+    }
+
+    #[test]
+    fn alpha_comes_from_the_other_image() {
+        let coverage = gradient(4, 2, 1.0);
+        let expected: Vec<u8> = (0..2)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .map(|(x, y)| (256.0 * coverage.pixel(x, y).x().min(0.999)) as u8)
+            .collect();
+
+        let image = gradient(4, 2, 0.5).with_alpha(&coverage);
+        let (_, alpha) = round_trip("alpha.png", image, true);
+        assert_eq!(alpha, Some(expected));
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(!crc32_update(!0, b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(&[]), 1);
+    }
+}
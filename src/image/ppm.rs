@@ -1,76 +1,74 @@
-use std::{fs::OpenOptions, io::Write};
+use std::{
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+};
 
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow, bail};
 
-use crate::utility::interval::Interval;
+use crate::vec3::Color;
 
-use super::{Image, ToFile, pixel::uPixel};
+use super::{FromFile, Image, ToFile, display::srgb_to_linear, pixel::uPixel};
 
+#[allow(clippy::upper_case_acronyms)]
 pub struct PPM {
     data: Vec<uPixel>,
     width: u32,
     height: u32,
+    binary: bool,
 }
 
-impl PPM {
-    fn linear_to_gama(linear_component: f64) -> f64 {
-        if linear_component > 0.0 {
-            return linear_component.powf(0.5);
-        }
-        return 0.0;
-    }
-}
-
-const INTENSITY: Interval = Interval {
-    min: 0.000,
-    max: 0.999,
-};
-
 impl From<Image> for PPM {
     fn from(value: Image) -> Self {
-        // Image stores pixels as values from 0.0 to 1.0
-        // PPM image format requires us to use values from 0 to 255
-        let converted = value
-            .data
-            .iter()
-            .map(|x| {
-                let red = Self::linear_to_gama(x.red);
-                let green = Self::linear_to_gama(x.green);
-                let blue = Self::linear_to_gama(x.blue);
-                let alpha = x.alpha;
-
-                uPixel {
-                    red: (256.0 * INTENSITY.clamp(red)) as u8,
-                    green: (256.0 * INTENSITY.clamp(green)) as u8,
-                    blue: (256.0 * INTENSITY.clamp(blue)) as u8,
-                    alpha: (256.0 * INTENSITY.clamp(alpha)) as u8,
-                }
-            })
-            .collect();
-
         PPM {
-            data: converted,
+            data: value.quantize(),
             width: value.width,
             height: value.height,
+            binary: false,
         }
     }
 }
 
+impl PPM {
+    // Write raw bytes (P6) instead of ASCII numbers (P3)
+    pub fn with_binary(mut self, binary: bool) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    // Turns a stored sample back into linear radiance. Values are taken from the middle of
+    // their quantization step and the sRGB encoding is undone, the inverse of quantize() with
+    // the default display transform
+    fn sample_to_linear(sample: u32, max_value: u32) -> f64 {
+        srgb_to_linear((sample as f64 + 0.5) / (max_value as f64 + 1.0))
+    }
+}
+
 impl ToFile for PPM {
     fn get_metadata(&self) -> String {
-        format!("P3\n{} {}\n255\n", self.width, self.height)
+        let magic = if self.binary { "P6" } else { "P3" };
+        format!("{}\n{} {}\n255\n", magic, self.width, self.height)
     }
 
     fn save(&self, image_path: &str) -> Result<()> {
         PPM::delete_if_exits(image_path)?;
 
-        match OpenOptions::new().write(true).create(true).open(image_path) {
-            Ok(mut open_file) => {
-                open_file.write(self.get_metadata().as_bytes())?;
-                for (_idx, upixel) in self.data.iter().enumerate() {
-                    let pixel = format!("{} {} {}\n", upixel.red, upixel.green, upixel.blue);
-                    open_file.write(pixel.as_bytes())?;
+        match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(image_path)
+        {
+            Ok(open_file) => {
+                let mut out = BufWriter::new(open_file);
+                out.write_all(self.get_metadata().as_bytes())?;
+                for upixel in self.data.iter() {
+                    if self.binary {
+                        out.write_all(&[upixel.red, upixel.green, upixel.blue])?;
+                    } else {
+                        writeln!(out, "{} {} {}", upixel.red, upixel.green, upixel.blue)?;
+                    }
                 }
+                out.flush()?;
                 Ok(())
             }
 
@@ -78,3 +76,208 @@ impl ToFile for PPM {
         }
     }
 }
+
+// Reads P2/P3 (ASCII) and P5/P6 (binary) files, grayscale is expanded to RGB
+impl FromFile for PPM {
+    fn load(image_path: &str) -> Result<Image> {
+        let bytes = std::fs::read(image_path)
+            .with_context(|| format!("Could not read image {}", image_path))?;
+        let mut header = Header::new(&bytes);
+
+        let magic = header.token()?;
+        let (channels, binary) = match magic {
+            b"P2" => (1, false),
+            b"P3" => (3, false),
+            b"P5" => (1, true),
+            b"P6" => (3, true),
+            _ => bail!("Not a PGM or PPM file."),
+        };
+
+        let width = header.number()?;
+        let height = header.number()?;
+        let max_value = header.number()?;
+        if max_value == 0 || max_value > 0xFFFF {
+            bail!("Invalid maximum sample value {}.", max_value);
+        }
+        if width == 0 || height == 0 {
+            bail!("Invalid image size {}x{}.", width, height);
+        }
+
+        let sample_count = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .ok_or_else(|| anyhow!("Invalid image size {}x{}.", width, height))?;
+        let samples: Vec<u32> = if binary {
+            // Exactly one whitespace character separates the header from the raster
+            let sample_size = if max_value < 256 { 1 } else { 2 };
+            let raster = bytes
+                .get(header.pos + 1..)
+                .filter(|raster| {
+                    sample_count
+                        .checked_mul(sample_size)
+                        .is_some_and(|size| raster.len() >= size)
+                })
+                .ok_or_else(|| anyhow!("Unexpected end of pixel data."))?;
+
+            if sample_size == 1 {
+                raster[..sample_count].iter().map(|b| *b as u32).collect()
+            } else {
+                raster
+                    .chunks_exact(2)
+                    .take(sample_count)
+                    .map(|b| u16::from_be_bytes([b[0], b[1]]) as u32)
+                    .collect()
+            }
+        } else {
+            (0..sample_count)
+                .map(|_| header.number())
+                .collect::<Result<_>>()?
+        };
+
+        if let Some(sample) = samples.iter().find(|s| **s > max_value) {
+            bail!(
+                "Sample {} is above the maximum value {}.",
+                sample,
+                max_value
+            );
+        }
+
+        let data = samples
+            .chunks_exact(channels)
+            .map(|pixel| {
+                let linear = |s: u32| PPM::sample_to_linear(s, max_value);
+                match pixel {
+                    [gray] => Color::new(linear(*gray), linear(*gray), linear(*gray)),
+                    _ => Color::new(linear(pixel[0]), linear(pixel[1]), linear(pixel[2])),
+                }
+            })
+            .collect();
+
+        let mut image = Image::new(width, height);
+        image.load_data(data);
+        Ok(image)
+    }
+}
+
+// Whitespace separated header tokens, skipping '#' comments
+struct Header<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Header<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn token(&mut self) -> Result<&'a [u8]> {
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'#') => {
+                    while self.bytes.get(self.pos).is_some_and(|b| *b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                Some(b) if b.is_ascii_whitespace() => self.pos += 1,
+                Some(_) => break,
+                None => bail!("Unexpected end of file."),
+            }
+        }
+
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| !b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+        Ok(&self.bytes[start..self.pos])
+    }
+
+    fn number(&mut self) -> Result<u32> {
+        let token = self.token()?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .ok_or_else(|| anyhow!("Invalid number {:?}.", String::from_utf8_lossy(token)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::image::{
+        Format,
+        display::linear_to_srgb,
+        testing::{assert_close, gradient, temp_file, temp_path},
+    };
+
+    use super::*;
+
+    // Half a step of 8 bit sRGB, plus some slack for the float math
+    const STEP: f64 = 0.5 / 256.0 + 1e-9;
+
+    fn round_trip(binary: bool) {
+        let path = temp_path(if binary { "binary.ppm" } else { "ascii.ppm" });
+        let format = Format::Ppm { binary };
+        gradient(13, 6, 0.95).save(&path, format, false).unwrap();
+        let loaded = PPM::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_close(&gradient(13, 6, 0.95), &loaded, STEP, linear_to_srgb);
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        round_trip(false);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        round_trip(true);
+    }
+
+    #[test]
+    fn reads_grayscale_and_16_bit_samples() {
+        let path = temp_file("gray.pgm", b"P5\n# comment\n2 1\n65535\n\xff\xff\x00\x00");
+        let image = PPM::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let white = image.pixel(0, 0);
+        assert!(white.e.iter().all(|c| (c - 1.0).abs() < 1e-4));
+        assert!(image.pixel(1, 0).e.iter().all(|c| *c < 1e-4));
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let cases: [(&str, &[u8], &str); 4] = [
+            ("zero.ppm", b"P3 0 4 255\n", "Invalid image size 0x4."),
+            (
+                "huge.ppm",
+                b"P6 4294967295 4294967295 255\n\0\0\0",
+                "Invalid image size 4294967295x4294967295.",
+            ),
+            (
+                "short.ppm",
+                b"P6 65535 65535 255\n\0\0\0",
+                "Unexpected end of pixel data.",
+            ),
+            (
+                "max.ppm",
+                b"P3 1 1 0\n0 0 0",
+                "Invalid maximum sample value 0.",
+            ),
+        ];
+        for (name, bytes, message) in cases {
+            let path = temp_file(name, bytes);
+            let result = PPM::load(&path);
+            fs::remove_file(&path).unwrap();
+            assert_eq!(
+                result.err().map(|e| e.to_string()).as_deref(),
+                Some(message)
+            );
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::f64::consts::PI;
+
+use crate::vec3::{Vec3, onb::Onb};
+
+use super::Pdf;
+
+// Cosine weighted hemisphere around a normal, the ideal density for Lambertian surfaces
+pub struct CosinePdf {
+    uvw: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(w: &Vec3) -> Self {
+        Self { uvw: Onb::new(w) }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        let cosine_theta = Vec3::dot(&Vec3::unit_vector(direction), &self.uvw.w());
+        f64::max(0.0, cosine_theta / PI)
+    }
+
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        self.uvw.transform(&Vec3::sample_cosine_direction(u))
+    }
+}
@@ -0,0 +1,47 @@
+use std::f64::consts::PI;
+
+use crate::vec3::Vec3;
+
+use super::Pdf;
+
+// Directions of a fuzzy reflection, the mirror direction offset by a random point on a sphere
+// of radius `fuzz` around its tip and normalized. Radius has to be in (0, 1]
+pub struct GlossyPdf {
+    reflected: Vec3, // Unit mirror direction
+    fuzz: f64,
+}
+
+impl GlossyPdf {
+    pub fn new(reflected: &Vec3, fuzz: f64) -> Self {
+        Self {
+            reflected: Vec3::unit_vector(reflected),
+            fuzz,
+        }
+    }
+}
+
+impl Pdf for GlossyPdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        // The ray along direction crosses the fuzz sphere at t^2 - 2bt + c = 0. Each crossing
+        // point x contributes the sphere's area density 1 / (4 pi fuzz^2) scaled by
+        // |x|^2 / cos, the cosine between the sphere normal and the ray being sqrt(disc) / fuzz
+        let direction = Vec3::unit_vector(direction);
+        let b = Vec3::dot(&direction, &self.reflected);
+        let c = 1.0 - self.fuzz * self.fuzz;
+        let disc = b * b - c;
+        if disc <= 0.0 {
+            return 0.0;
+        }
+
+        let sqrt_disc = f64::sqrt(disc);
+        [b - sqrt_disc, b + sqrt_disc]
+            .into_iter()
+            .filter(|t| *t > 0.0)
+            .map(|t| t * t / (4.0 * PI * self.fuzz * sqrt_disc))
+            .sum()
+    }
+
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        Vec3::unit_vector(&(self.reflected + self.fuzz * Vec3::sample_unit_sphere(u)))
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{
+    hittable::Hittable,
+    vec3::{Point3, Vec3},
+};
+
+use super::Pdf;
+
+// Directions from a point towards some geometry, typically the lights of a scene
+pub struct HittablePdf<'a> {
+    objects: &'a dyn Hittable,
+    origin: Point3,
+    time: f64, // When the sampled rays leave, for moving geometry
+}
+
+impl<'a> HittablePdf<'a> {
+    pub fn new(objects: &'a dyn Hittable, origin: Point3, time: f64) -> Self {
+        Self {
+            objects,
+            origin,
+            time,
+        }
+    }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn value(&self, direction: &Vec3) -> f64 {
+        self.objects.pdf_value(&self.origin, direction, self.time)
+    }
+
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        self.objects.random(&self.origin, self.time, u)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::vec3::Vec3;
+
+use super::Pdf;
+
+// Even blend of two densities, samples either one with equal probability. The first half of
+// the sample range picks the first density, and is stretched back to [0, 1) before use
+pub struct MixturePdf<'a> {
+    p: [&'a dyn Pdf; 2],
+}
+
+impl<'a> MixturePdf<'a> {
+    pub fn new(p0: &'a dyn Pdf, p1: &'a dyn Pdf) -> Self {
+        Self { p: [p0, p1] }
+    }
+}
+
+impl Pdf for MixturePdf<'_> {
+    fn value(&self, direction: &Vec3) -> f64 {
+        0.5 * self.p[0].value(direction) + 0.5 * self.p[1].value(direction)
+    }
+
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        if u.0 < 0.5 {
+            self.p[0].generate((2.0 * u.0, u.1))
+        } else {
+            self.p[1].generate((2.0 * u.0 - 1.0, u.1))
+        }
+    }
+}
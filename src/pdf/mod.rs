@@ -0,0 +1,25 @@
+use crate::vec3::Vec3;
+
+pub mod cosine;
+pub mod glossy;
+pub mod hittable;
+pub mod mixture;
+pub mod sphere;
+
+// Probability density over directions, used to importance sample scattered rays. Directions
+// drawn with `generate` have to be distributed according to `value`
+pub trait Pdf {
+    // Density with respect to solid angle, `direction` does not have to be normalized
+    fn value(&self, direction: &Vec3) -> f64;
+
+    // Maps a sample from [0, 1)^2 to a direction, well spread samples give well spread directions
+    fn generate(&self, u: (f64, f64)) -> Vec3;
+}
+
+// Weight for a sample drawn with density `pdf` when `other_pdf` could have produced it as well,
+// the weights of both strategies for the same direction add up to one
+pub fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let a = pdf * pdf;
+    let b = other_pdf * other_pdf;
+    if a + b == 0.0 { 0.0 } else { a / (a + b) }
+}
@@ -0,0 +1,18 @@
+use std::f64::consts::PI;
+
+use crate::vec3::Vec3;
+
+use super::Pdf;
+
+// Uniform density over all directions
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: &Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn generate(&self, u: (f64, f64)) -> Vec3 {
+        Vec3::sample_unit_sphere(u)
+    }
+}
@@ -1,14 +1,19 @@
 use crate::vec3::{Point3, Vec3};
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 pub struct Ray {
     orig: Point3,
     dir: Vec3,
+    tm: f64, // Moment within the shutter interval the ray was sent at
 }
 
 impl Ray {
     pub fn new(orig: Point3, dir: Vec3) -> Self {
-        Ray { orig, dir }
+        Ray { orig, dir, tm: 0.0 }
+    }
+
+    pub fn with_time(orig: Point3, dir: Vec3, tm: f64) -> Self {
+        Ray { orig, dir, tm }
     }
 
     pub fn origin(&self) -> &Point3 {
@@ -19,6 +24,10 @@ impl Ray {
         &self.dir
     }
 
+    pub fn time(&self) -> f64 {
+        self.tm
+    }
+
     pub fn at(&self, t: f64) -> Point3 {
         self.orig + t * self.dir
     }
@@ -0,0 +1,85 @@
+use crate::utility::{hash, random_double};
+
+use super::{Sampler, to_unit};
+
+// Bases of the first dimensions, beyond them the points get too regular to be of use and
+// plain random numbers take over
+const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193,
+    197, 199, 211, 223, 227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307,
+    311,
+];
+
+// Halton sequence, dimension d is the radical inverse of the sample index in the d-th prime.
+// Each pixel walks the same sequence, shifted by a random offset per pixel and dimension
+// (Cranley-Patterson rotation) so neighbouring pixels do not repeat each other.
+pub struct HaltonSampler {
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: usize,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn next(&mut self) -> f64 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+
+        let Some(&base) = PRIMES.get(dimension) else {
+            return random_double();
+        };
+
+        let key = hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            dimension as u64,
+        ]);
+        let value = radical_inverse(base, self.index) + to_unit(key as u32);
+        if value >= 1.0 { value - 1.0 } else { value }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, i: u32, j: u32, index: u32) {
+        self.pixel = (i, j);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.next()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.next(), self.next())
+    }
+}
+
+// Mirrors the digits of index in the given base around the radix point
+fn radical_inverse(base: u32, mut index: u32) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_n = 1.0;
+    let mut reversed: u64 = 0;
+
+    while index > 0 {
+        let next = index / base;
+        let digit = index - next * base;
+        reversed = reversed * base as u64 + digit as u64;
+        inv_base_n *= inv_base;
+        index = next;
+    }
+
+    // Rounding can land exactly on one, keep the result in [0, 1)
+    f64::min(reversed as f64 * inv_base_n, 1.0 - f64::EPSILON / 2.0)
+}
@@ -0,0 +1,18 @@
+use crate::utility::random_double;
+
+use super::Sampler;
+
+// Every value is drawn on its own from the thread's random generator
+pub struct IndependentSampler;
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, _i: u32, _j: u32, _index: u32) {}
+
+    fn get_1d(&mut self) -> f64 {
+        random_double()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (random_double(), random_double())
+    }
+}
@@ -0,0 +1,62 @@
+use halton::HaltonSampler;
+use independent::IndependentSampler;
+use sobol::SobolSampler;
+use stratified::StratifiedSampler;
+
+pub mod halton;
+pub mod independent;
+pub mod sobol;
+pub mod stratified;
+
+// Source of the sample values for one camera ray and the path it starts. Every call hands out
+// the next dimension of the current sample, so the n-th value of each sample in a pixel comes
+// from the same well distributed point set.
+pub trait Sampler {
+    // Called before each camera ray, `index` counts the samples within pixel (i, j)
+    fn start_pixel_sample(&mut self, i: u32, j: u32, index: u32);
+
+    // Value in [0, 1)
+    fn get_1d(&mut self) -> f64;
+
+    // Point in [0, 1)^2, both coordinates come from the same dimension pair
+    fn get_2d(&mut self) -> (f64, f64);
+}
+
+// Which sampler a render uses
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SamplerKind {
+    #[default]
+    Independent, // Plain random numbers
+    Stratified, // One jittered sample in each cell of a grid, per dimension
+    Halton,     // Radical inverses in prime bases, randomly shifted per pixel
+    Sobol,      // Owen scrambled Sobol points, shuffled per pixel and dimension pair
+}
+
+impl SamplerKind {
+    pub const NAMES: [&str; 4] = ["independent", "stratified", "halton", "sobol"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "independent" => Some(SamplerKind::Independent),
+            "stratified" => Some(SamplerKind::Stratified),
+            "halton" => Some(SamplerKind::Halton),
+            "sobol" => Some(SamplerKind::Sobol),
+            _ => None,
+        }
+    }
+
+    // Samplers are cheap and not shared, every render thread makes its own
+    pub fn create(&self, samples_per_pixel: u32, seed: u64) -> Box<dyn Sampler> {
+        match self {
+            SamplerKind::Independent => Box::new(IndependentSampler),
+            SamplerKind::Stratified => Box::new(StratifiedSampler::new(samples_per_pixel, seed)),
+            SamplerKind::Halton => Box::new(HaltonSampler::new(seed)),
+            SamplerKind::Sobol => Box::new(SobolSampler::new(seed)),
+        }
+    }
+}
+
+// Maps 32 random bits to [0, 1)
+fn to_unit(bits: u32) -> f64 {
+    bits as f64 / 4_294_967_296.0
+}
@@ -0,0 +1,97 @@
+use crate::utility::hash;
+
+use super::{Sampler, to_unit};
+
+// Sobol points with hash based Owen scrambling (Burley, "Practical Hash-based Owen
+// Scrambling"). Only the first two Sobol dimensions are used: every dimension pair of a sample
+// takes the same 2D point set, shuffled and scrambled with its own seed, which keeps each pair
+// well stratified without a table of direction numbers for high dimensions.
+pub struct SobolSampler {
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn key(&mut self) -> u64 {
+        let key = hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+        ]);
+        self.dimension += 1;
+        key
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, i: u32, j: u32, index: u32) {
+        self.pixel = (i, j);
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let key = self.key();
+        let index = nested_uniform_scramble(self.index, key as u32);
+        to_unit(nested_uniform_scramble(sobol(index, 0), (key >> 32) as u32))
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let key = self.key();
+        let index = nested_uniform_scramble(self.index, key as u32);
+        let scramble = hash(&[key]);
+        (
+            to_unit(nested_uniform_scramble(sobol(index, 0), scramble as u32)),
+            to_unit(nested_uniform_scramble(
+                sobol(index, 1),
+                (scramble >> 32) as u32,
+            )),
+        )
+    }
+}
+
+// Unscrambled Sobol point, dimension 0 is the van der Corput sequence and dimension 1 uses
+// the direction numbers of the polynomial x + 1
+fn sobol(index: u32, dimension: u32) -> u32 {
+    let mut result = 0;
+    let mut direction: u32 = 1 << 31;
+
+    for bit in 0..32 {
+        if (index >> bit) & 1 == 1 {
+            result ^= direction;
+        }
+        direction = match dimension {
+            0 => direction >> 1,
+            _ => direction ^ (direction >> 1),
+        };
+    }
+
+    result
+}
+
+// Owen scrambling of all bits, flips each bit based on a hash of the bits above it
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+}
+
+// Hash in which every bit only depends on the bits below it
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    x
+}
@@ -0,0 +1,108 @@
+use crate::utility::{hash, random_double};
+
+use super::Sampler;
+
+// Splits every dimension into as many strata as there are samples per pixel and puts one
+// jittered sample in each. 2D samples use an x by y grid with x * y = samples_per_pixel.
+// Which sample lands in which stratum is shuffled per pixel and dimension, so dimensions do
+// not line up with each other.
+pub struct StratifiedSampler {
+    samples_per_pixel: u32,
+    grid: (u32, u32),
+    seed: u64,
+    pixel: (u32, u32),
+    index: u32,
+    dimension: u32,
+}
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: u32, seed: u64) -> Self {
+        // Squarest grid that uses every sample, a prime count degenerates to a single row
+        let samples_per_pixel = samples_per_pixel.max(1);
+        let mut x = (samples_per_pixel as f64).sqrt() as u32;
+        while !samples_per_pixel.is_multiple_of(x) {
+            x -= 1;
+        }
+
+        Self {
+            samples_per_pixel,
+            grid: (x, samples_per_pixel / x),
+            seed,
+            pixel: (0, 0),
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    // Stratum of the current sample in the current dimension
+    fn stratum(&mut self) -> u32 {
+        let key = hash(&[
+            self.seed,
+            self.pixel.0 as u64,
+            self.pixel.1 as u64,
+            self.dimension as u64,
+        ]);
+        self.dimension += 1;
+        permutation_element(self.index, self.samples_per_pixel, key as u32)
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, i: u32, j: u32, index: u32) {
+        self.pixel = (i, j);
+        self.index = index % self.samples_per_pixel;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let stratum = self.stratum();
+        (stratum as f64 + random_double()) / self.samples_per_pixel as f64
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let stratum = self.stratum();
+        let (x, y) = (stratum % self.grid.0, stratum / self.grid.0);
+        (
+            (x as f64 + random_double()) / self.grid.0 as f64,
+            (y as f64 + random_double()) / self.grid.1 as f64,
+        )
+    }
+}
+
+// Element i of a random permutation of 0..n picked by the seed p, without building the
+// permutation (Kensler, "Correlated Multi-Jittered Sampling")
+fn permutation_element(mut i: u32, n: u32, p: u32) -> u32 {
+    let mut w = n - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    // Permutes within the next power of two and retries until the result is in range
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < n {
+            break;
+        }
+    }
+
+    ((i as u64 + p as u64) % n as u64) as u32
+}
@@ -0,0 +1,623 @@
+use std::sync::Arc;
+
+use crate::{
+    camera::{Background, Camera},
+    hittable::{
+        bvh::{BvhNode, SplitMethod},
+        constant_medium::ConstantMedium,
+        hittable_list::HittableList,
+        material::{
+            Material, dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian,
+            metal::Metal,
+        },
+        quad::{Quad, make_box},
+        sphere::Sphere,
+        transform::Transform,
+    },
+    texture::{checker::CheckerTexture, noise::MarbleTexture},
+    utility::{random_double, random_double_clamp},
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::Scene;
+
+// Names accepted by `by_name`, the first one is the default scene
+pub const NAMES: &[&str] = &[
+    "first_book_finale",
+    "three_spheres",
+    "simple_light",
+    "cornell_box",
+    "cornell_smoke",
+    "checkered_spheres",
+    "perlin_spheres",
+    "bouncing_spheres",
+];
+
+pub fn by_name(name: &str) -> Option<Scene> {
+    match name {
+        "first_book_finale" => Some(first_book_finale()),
+        "three_spheres" => Some(three_spheres()),
+        "simple_light" => Some(simple_light()),
+        "cornell_box" => Some(cornell_box()),
+        "cornell_smoke" => Some(cornell_smoke()),
+        "checkered_spheres" => Some(checkered_spheres()),
+        "perlin_spheres" => Some(perlin_spheres()),
+        "bouncing_spheres" => Some(bouncing_spheres()),
+        _ => None,
+    }
+}
+
+fn three_spheres() -> Scene {
+    let mut world = HittableList::default();
+    let mut camera = Camera::default();
+
+    // Camera setup
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 400;
+    camera.samples_per_pixel = 300;
+    camera.max_depth = 50;
+
+    camera.v_fov = 30.0;
+    camera.lookfrom = Point3::new(-2.0, 2.0, 1.0);
+    camera.lookat = Point3::new(0.0, 0.0, -1.0);
+    camera.vup = Point3::new(0.0, 1.0, 0.0);
+
+    camera.defocus_angle = 10.0;
+    camera.focus_dist = 3.4;
+
+    // Old test code
+    let material_ground = Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.0)));
+    let material_center = Arc::new(Lambertian::new(Color::new(0.1, 0.2, 0.5)));
+    let material_left = Arc::new(Dielectric::new(1.5));
+    let material_bubble = Arc::new(Dielectric::new(1.00 / 1.50));
+    let material_right = Arc::new(Metal::new(Color::new(0.8, 0.8, 0.0), 1.0));
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, -100.5, -1.0),
+        100.0,
+        material_ground,
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, 0.0, -1.2),
+        0.5,
+        material_center,
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(-1.0, 0.0, -1.0),
+        0.5,
+        material_left,
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(-1.0, 0.0, -1.0),
+        0.4,
+        material_bubble,
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(1.0, 0.0, -1.0),
+        0.5,
+        material_right,
+    )));
+
+    Scene {
+        world: world.tag_objects(),
+        lights: HittableList::default(),
+        camera,
+    }
+}
+
+fn first_book_finale() -> Scene {
+    let mut world = HittableList::default();
+    let mut camera = Camera::default();
+
+    // Camera setup
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 1200;
+    camera.samples_per_pixel = 500;
+    camera.max_depth = 50;
+
+    camera.v_fov = 20.0;
+    camera.lookfrom = Point3::new(13.0, 2.0, 3.0);
+    camera.lookat = Point3::new(0.0, 0.0, 0.0);
+    camera.vup = Point3::new(0.0, 1.0, 0.0);
+
+    camera.defocus_angle = 0.6;
+    camera.focus_dist = 10.0;
+
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        ground_material,
+    )));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat = random_double();
+            let center = Point3::new(
+                a as f64 + 0.9 * random_double(),
+                0.2,
+                b as f64 + 0.9 * random_double(),
+            );
+
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                let sphere_material: Arc<dyn Material> = if choose_mat < 0.8 {
+                    // Diffuse
+                    let albedo = Color::random() * Color::random();
+                    Arc::new(Lambertian::new(albedo))
+                } else if choose_mat < 0.95 {
+                    // Metal
+                    let albedo = Color::random_clamp(0.5, 1.0);
+                    let fuzz = random_double_clamp(0.0, 0.5);
+                    Arc::new(Metal::new(albedo, fuzz))
+                } else {
+                    // Glass
+                    Arc::new(Dielectric::new(1.5))
+                };
+
+                world.add(Arc::new(Sphere::new(center, 0.2, sphere_material)));
+            }
+        }
+    }
+
+    let material1 = Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        material1,
+    )));
+
+    let material2 = Arc::new(Dielectric::new(1.5));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(-4.0, 1.0, 0.0),
+        1.0,
+        material2,
+    )));
+
+    let material3 = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(4.0, 1.0, 0.0),
+        1.0,
+        material3,
+    )));
+
+    // Wrap the scene in a BVH so each ray only tests the objects it can actually reach
+    let world = HittableList::new(Arc::new(BvhNode::new(
+        world.tag_objects(),
+        SplitMethod::Sah,
+    )));
+
+    Scene {
+        world,
+        lights: HittableList::default(),
+        camera,
+    }
+}
+
+fn simple_light() -> Scene {
+    let mut world = HittableList::default();
+    let mut camera = Camera::default();
+
+    // Camera setup, the black background leaves the glowing spheres as the only light
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 400;
+    camera.samples_per_pixel = 500;
+    camera.max_depth = 50;
+    camera.background = Background::Solid(Color::default());
+
+    camera.v_fov = 20.0;
+    camera.lookfrom = Point3::new(26.0, 3.0, 6.0);
+    camera.lookat = Point3::new(0.0, 2.0, 0.0);
+    camera.vup = Point3::new(0.0, 1.0, 0.0);
+
+    camera.defocus_angle = 0.0;
+
+    let ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        ground,
+    )));
+
+    let diffuse = Arc::new(Lambertian::new(Color::new(0.7, 0.3, 0.2)));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, 2.0, 0.0),
+        2.0,
+        diffuse,
+    )));
+
+    // Both glowing spheres are also sampled directly
+    let mut lights = HittableList::default();
+    let light = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+    let top_light = Arc::new(Sphere::new(Point3::new(0.0, 7.0, 0.0), 2.0, light.clone()));
+    let side_light = Arc::new(Sphere::new(Point3::new(4.0, 1.0, -3.0), 0.7, light));
+    world.add(top_light.clone());
+    world.add(side_light.clone());
+    lights.add(top_light);
+    lights.add(side_light);
+
+    Scene {
+        world: world.tag_objects(),
+        lights,
+        camera,
+    }
+}
+
+fn cornell_box() -> Scene {
+    let mut world = HittableList::default();
+    let mut camera = Camera::default();
+
+    // Camera setup
+    camera.aspect_ratio = 1.0;
+    camera.image_width = 600;
+    camera.samples_per_pixel = 200;
+    camera.max_depth = 50;
+    camera.background = Background::Solid(Color::default());
+
+    camera.v_fov = 40.0;
+    camera.lookfrom = Point3::new(278.0, 278.0, -800.0);
+    camera.lookat = Point3::new(278.0, 278.0, 0.0);
+    camera.vup = Point3::new(0.0, 1.0, 0.0);
+
+    camera.defocus_angle = 0.0;
+
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0)));
+
+    world.add(Arc::new(Quad::new(
+        Point3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        green,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        red,
+    )));
+    let ceiling_light = Arc::new(Quad::new(
+        Point3::new(343.0, 554.0, 332.0),
+        Vec3::new(-130.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -105.0),
+        light,
+    ));
+    world.add(ceiling_light.clone());
+    let lights = HittableList::new(ceiling_light);
+    world.add(Arc::new(Quad::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        white.clone(),
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(555.0, 555.0, 555.0),
+        Vec3::new(-555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, -555.0),
+        white.clone(),
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(0.0, 0.0, 555.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        white.clone(),
+    )));
+
+    let box1 = make_box(
+        &Point3::new(0.0, 0.0, 0.0),
+        &Point3::new(165.0, 330.0, 165.0),
+        white.clone(),
+    );
+    world.add(Arc::new(
+        Transform::new(Arc::new(box1))
+            .rotate_y(15.0)
+            .translate(Vec3::new(265.0, 0.0, 295.0)),
+    ));
+
+    let box2 = make_box(
+        &Point3::new(0.0, 0.0, 0.0),
+        &Point3::new(165.0, 165.0, 165.0),
+        white,
+    );
+    world.add(Arc::new(
+        Transform::new(Arc::new(box2))
+            .rotate_y(-18.0)
+            .translate(Vec3::new(130.0, 0.0, 65.0)),
+    ));
+
+    let world = HittableList::new(Arc::new(BvhNode::new(
+        world.tag_objects(),
+        SplitMethod::Sah,
+    )));
+
+    Scene {
+        world,
+        lights,
+        camera,
+    }
+}
+
+fn cornell_smoke() -> Scene {
+    let mut world = HittableList::default();
+    let mut camera = Camera::default();
+
+    // Camera setup
+    camera.aspect_ratio = 1.0;
+    camera.image_width = 600;
+    camera.samples_per_pixel = 200;
+    camera.max_depth = 50;
+    camera.background = Background::Solid(Color::default());
+
+    camera.v_fov = 40.0;
+    camera.lookfrom = Point3::new(278.0, 278.0, -800.0);
+    camera.lookat = Point3::new(278.0, 278.0, 0.0);
+    camera.vup = Point3::new(0.0, 1.0, 0.0);
+
+    camera.defocus_angle = 0.0;
+
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Color::new(7.0, 7.0, 7.0)));
+
+    world.add(Arc::new(Quad::new(
+        Point3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        green,
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        red,
+    )));
+    let ceiling_light = Arc::new(Quad::new(
+        Point3::new(113.0, 554.0, 127.0),
+        Vec3::new(330.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 305.0),
+        light,
+    ));
+    world.add(ceiling_light.clone());
+    let lights = HittableList::new(ceiling_light);
+    world.add(Arc::new(Quad::new(
+        Point3::new(0.0, 555.0, 0.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        white.clone(),
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 555.0),
+        white.clone(),
+    )));
+    world.add(Arc::new(Quad::new(
+        Point3::new(0.0, 0.0, 555.0),
+        Vec3::new(555.0, 0.0, 0.0),
+        Vec3::new(0.0, 555.0, 0.0),
+        white.clone(),
+    )));
+
+    // The two boxes from the Cornell box filled with dark smoke and light fog
+    let box1 = make_box(
+        &Point3::new(0.0, 0.0, 0.0),
+        &Point3::new(165.0, 330.0, 165.0),
+        white.clone(),
+    );
+    let box1 = Transform::new(Arc::new(box1))
+        .rotate_y(15.0)
+        .translate(Vec3::new(265.0, 0.0, 295.0));
+    world.add(Arc::new(ConstantMedium::new(
+        Arc::new(box1),
+        0.01,
+        Color::new(0.0, 0.0, 0.0),
+    )));
+
+    let box2 = make_box(
+        &Point3::new(0.0, 0.0, 0.0),
+        &Point3::new(165.0, 165.0, 165.0),
+        white,
+    );
+    let box2 = Transform::new(Arc::new(box2))
+        .rotate_y(-18.0)
+        .translate(Vec3::new(130.0, 0.0, 65.0));
+    world.add(Arc::new(ConstantMedium::new(
+        Arc::new(box2),
+        0.01,
+        Color::new(1.0, 1.0, 1.0),
+    )));
+
+    let world = HittableList::new(Arc::new(BvhNode::new(
+        world.tag_objects(),
+        SplitMethod::Sah,
+    )));
+
+    Scene {
+        world,
+        lights,
+        camera,
+    }
+}
+
+fn checkered_spheres() -> Scene {
+    let mut world = HittableList::default();
+    let mut camera = Camera::default();
+
+    // Camera setup
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 400;
+    camera.samples_per_pixel = 100;
+    camera.max_depth = 50;
+
+    camera.v_fov = 20.0;
+    camera.lookfrom = Point3::new(13.0, 2.0, 3.0);
+    camera.lookat = Point3::new(0.0, 0.0, 0.0);
+    camera.vup = Point3::new(0.0, 1.0, 0.0);
+
+    camera.defocus_angle = 0.0;
+
+    let checker = Arc::new(CheckerTexture::from_colors(
+        0.32,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, -10.0, 0.0),
+        10.0,
+        Arc::new(Lambertian::from_texture(checker.clone())),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, 10.0, 0.0),
+        10.0,
+        Arc::new(Lambertian::from_texture(checker)),
+    )));
+
+    Scene {
+        world: world.tag_objects(),
+        lights: HittableList::default(),
+        camera,
+    }
+}
+
+fn perlin_spheres() -> Scene {
+    let mut world = HittableList::default();
+    let mut camera = Camera::default();
+
+    // Camera setup
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 400;
+    camera.samples_per_pixel = 100;
+    camera.max_depth = 50;
+
+    camera.v_fov = 20.0;
+    camera.lookfrom = Point3::new(13.0, 2.0, 3.0);
+    camera.lookat = Point3::new(0.0, 0.0, 0.0);
+    camera.vup = Point3::new(0.0, 1.0, 0.0);
+
+    camera.defocus_angle = 0.0;
+
+    // Fixed noise seed, the marble veins stay put between renders
+    let pertext = Arc::new(MarbleTexture::new(0, 4.0, 7));
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(pertext.clone())),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, 2.0, 0.0),
+        2.0,
+        Arc::new(Lambertian::from_texture(pertext)),
+    )));
+
+    Scene {
+        world: world.tag_objects(),
+        lights: HittableList::default(),
+        camera,
+    }
+}
+
+// First book finale where the diffuse spheres jump up while the shutter is open
+fn bouncing_spheres() -> Scene {
+    let mut world = HittableList::default();
+    let mut camera = Camera::default();
+
+    // Camera setup
+    camera.aspect_ratio = 16.0 / 9.0;
+    camera.image_width = 400;
+    camera.samples_per_pixel = 100;
+    camera.max_depth = 50;
+
+    camera.v_fov = 20.0;
+    camera.lookfrom = Point3::new(13.0, 2.0, 3.0);
+    camera.lookat = Point3::new(0.0, 0.0, 0.0);
+    camera.vup = Point3::new(0.0, 1.0, 0.0);
+
+    camera.defocus_angle = 0.6;
+    camera.focus_dist = 10.0;
+
+    camera.shutter_open = 0.0;
+    camera.shutter_close = 1.0;
+
+    let checker = Arc::new(CheckerTexture::from_colors(
+        0.32,
+        Color::new(0.2, 0.3, 0.1),
+        Color::new(0.9, 0.9, 0.9),
+    ));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::from_texture(checker)),
+    )));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat = random_double();
+            let center = Point3::new(
+                a as f64 + 0.9 * random_double(),
+                0.2,
+                b as f64 + 0.9 * random_double(),
+            );
+
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                if choose_mat < 0.8 {
+                    // Diffuse, bouncing
+                    let albedo = Color::random() * Color::random();
+                    let center2 = center + Vec3::new(0.0, random_double_clamp(0.0, 0.5), 0.0);
+                    world.add(Arc::new(Sphere::new_moving(
+                        center,
+                        center2,
+                        0.2,
+                        Arc::new(Lambertian::new(albedo)),
+                    )));
+                } else if choose_mat < 0.95 {
+                    // Metal
+                    let albedo = Color::random_clamp(0.5, 1.0);
+                    let fuzz = random_double_clamp(0.0, 0.5);
+                    world.add(Arc::new(Sphere::new(
+                        center,
+                        0.2,
+                        Arc::new(Metal::new(albedo, fuzz)),
+                    )));
+                } else {
+                    // Glass
+                    world.add(Arc::new(Sphere::new(
+                        center,
+                        0.2,
+                        Arc::new(Dielectric::new(1.5)),
+                    )));
+                }
+            }
+        }
+    }
+
+    world.add(Arc::new(Sphere::new(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Dielectric::new(1.5)),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(-4.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1))),
+    )));
+    world.add(Arc::new(Sphere::new(
+        Point3::new(4.0, 1.0, 0.0),
+        1.0,
+        Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0)),
+    )));
+
+    let world = HittableList::new(Arc::new(BvhNode::new(
+        world.tag_objects(),
+        SplitMethod::Sah,
+    )));
+
+    Scene {
+        world,
+        lights: HittableList::default(),
+        camera,
+    }
+}
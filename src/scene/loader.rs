@@ -0,0 +1,613 @@
+// Loader for the plain text scene format.
+//
+// A scene file is a list of statements, one per line. Anything after '#' is a comment.
+//
+//   camera <field> <value...>                     e.g. `camera lookfrom 13 2 3`
+//   camera background sky | <r> <g> <b>
+//   camera integrator naive | mis
+//   camera sampler independent | stratified | halton | sobol
+//   camera seed <n>                               same seed, same image, bit for bit
+//   camera filter box | tent | gaussian | mitchell | lanczos [radius in pixels]
+//   camera roulette_depth <n>                     bounces before paths can end at random
+//   camera samples_per_pass <n>                   progressive passes, 0 renders in one pass
+//   texture <name> solid <r> <g> <b>
+//   texture <name> checker <scale> <even texture> <odd texture>
+//   texture <name> image <path>                   PPM, PGM, HDR or PFM
+//   texture <name> noise <scale> [seed]
+//   texture <name> turbulence <scale> <octaves> [seed]
+//   texture <name> marble <scale> <octaves> [seed]
+//   material <name> lambertian <albedo>
+//   material <name> metal <albedo> <fuzz>
+//   material <name> dielectric <refraction_index>
+//   material <name> diffuse_light <albedo>        emitted radiance, may go above 1
+//   sphere <x> <y> <z> <radius> <material>
+//   moving_sphere <center at time 0 xyz> <center at time 1 xyz> <radius> <material>
+//   quad <corner xyz> <edge u xyz> <edge v xyz> <material>
+//   box <corner a xyz> <opposite corner b xyz> <material>
+//   triangle <a xyz> <b xyz> <c xyz> <material>
+//   mesh <path.obj> [material]                    without a material the .mtl files are used
+//   medium <object> <density> <albedo>            volume filling a declared, closed object
+//   light sphere|moving_sphere|quad|box|triangle <...>
+//                                                 places the object and samples it as a light
+//   object <name> <any object statement above>    declares an object without placing it
+//   instance <name> [<transform>...]              places a declared object, transforms are
+//                                                 applied in order and can be any of
+//                                                   translate <x> <y> <z>
+//                                                   rotate x|y|z <degrees>
+//                                                   scale <x> <y> <z>
+//   bvh sah | median | none                       acceleration structure, sah by default
+//
+// An <albedo> is either a color given as <r> <g> <b> or the name of a texture. Textures and
+// materials have to be declared before they are used. Relative paths are resolved from the
+// directory of the scene file. Noise textures use seed 0 unless told otherwise.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{
+    camera::{
+        Background, Camera, Integrator,
+        filter::{Filter, FilterKind},
+    },
+    hittable::{
+        Hittable,
+        bvh::{BvhNode, SplitMethod},
+        constant_medium::ConstantMedium,
+        hittable_list::HittableList,
+        material::{
+            Material, dielectric::Dielectric, diffuse_light::DiffuseLight, isotropic::Isotropic,
+            lambertian::Lambertian, metal::Metal,
+        },
+        quad::{Quad, make_box},
+        sphere::Sphere,
+        transform::Transform,
+        triangle::Triangle,
+    },
+    sampler::SamplerKind,
+    texture::{
+        Texture,
+        checker::CheckerTexture,
+        image_texture::ImageTexture,
+        noise::{MarbleTexture, NoiseTexture, TurbulenceTexture},
+        solid_color::SolidColor,
+    },
+    utility::parse_ratio,
+    vec3::{Color, Point3, Vec3},
+};
+
+use super::{Scene, obj};
+
+pub fn load(path: &str) -> Result<Scene> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read scene file {}", path))?;
+
+    let base_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+    parse(&source, base_dir).with_context(|| format!("Invalid scene file {}", path))
+}
+
+pub fn parse(source: &str, base_dir: &Path) -> Result<Scene> {
+    let mut builder = SceneBuilder::new(base_dir);
+
+    for (idx, line) in source.lines().enumerate() {
+        // Strip comments and surrounding whitespace
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        builder
+            .statement(line)
+            .map_err(|err| anyhow!("line {}: {}", idx + 1, err))?;
+    }
+
+    builder.finish()
+}
+
+struct SceneBuilder {
+    base_dir: PathBuf,
+    camera: Camera,
+    world: HittableList,
+    lights: HittableList,
+    textures: HashMap<String, Arc<dyn Texture>>,
+    materials: HashMap<String, Arc<dyn Material>>,
+    objects: HashMap<String, Arc<dyn Hittable>>, // Declared with `object`, placed with `instance`
+    bvh: Option<SplitMethod>,                    // None renders the plain object list
+}
+
+impl SceneBuilder {
+    fn new(base_dir: &Path) -> Self {
+        Self {
+            base_dir: base_dir.to_path_buf(),
+            camera: Camera::default(),
+            world: HittableList::default(),
+            lights: HittableList::default(),
+            textures: HashMap::new(),
+            materials: HashMap::new(),
+            objects: HashMap::new(),
+            bvh: Some(SplitMethod::Sah),
+        }
+    }
+}
+
+impl SceneBuilder {
+    fn statement(&mut self, line: &str) -> Result<()> {
+        let mut tokens = Tokens::new(line);
+
+        match tokens.word("statement")? {
+            "camera" => self.camera_field(&mut tokens)?,
+            "texture" => self.texture(&mut tokens)?,
+            "material" => self.material(&mut tokens)?,
+            "light" => self.light(&mut tokens)?,
+            "object" => self.object(&mut tokens)?,
+            "instance" => self.instance(&mut tokens)?,
+            "bvh" => self.bvh(&mut tokens)?,
+            kind => {
+                let object = self.geometry(kind, &mut tokens)?;
+                self.world.add(object);
+            }
+        }
+
+        tokens.finish()
+    }
+
+    fn camera_field(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let camera = &mut self.camera;
+
+        match tokens.word("camera field")? {
+            "aspect_ratio" => camera.aspect_ratio = tokens.ratio("aspect_ratio")?,
+            "image_width" => camera.image_width = tokens.positive("image_width")?,
+            "samples_per_pixel" => {
+                camera.samples_per_pixel = tokens.positive("samples_per_pixel")?
+            }
+            "adaptive_threshold" => {
+                camera.adaptive_threshold = tokens.non_negative("adaptive_threshold")?
+            }
+            "min_samples_per_pixel" => {
+                camera.min_samples_per_pixel = tokens.positive("min_samples_per_pixel")?
+            }
+            "max_samples_per_pixel" => {
+                camera.max_samples_per_pixel = tokens.positive("max_samples_per_pixel")?
+            }
+            "samples_per_pass" => camera.samples_per_pass = tokens.parse("samples_per_pass")?,
+            "max_depth" => camera.max_depth = tokens.parse("max_depth")?,
+            "roulette_depth" => camera.roulette_depth = tokens.parse("roulette_depth")?,
+            "background" => camera.background = tokens.background()?,
+            "sampler" => {
+                let name = tokens.word("sampler")?;
+                camera.sampler = SamplerKind::from_name(name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown sampler '{}', expected one of: {}",
+                        name,
+                        SamplerKind::NAMES.join(", ")
+                    )
+                })?;
+            }
+            "filter" => {
+                let name = tokens.word("filter")?;
+                let kind = FilterKind::from_name(name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown filter '{}', expected one of: {}",
+                        name,
+                        FilterKind::NAMES.join(", ")
+                    )
+                })?;
+                camera.filter = Filter::new(kind);
+                if tokens.peek().is_some() {
+                    camera.filter.radius = tokens.positive_f64("filter radius")?;
+                }
+            }
+            "integrator" => {
+                let name = tokens.word("integrator")?;
+                camera.integrator = Integrator::from_name(name).ok_or_else(|| {
+                    anyhow!(
+                        "unknown integrator '{}', expected one of: {}",
+                        name,
+                        Integrator::NAMES.join(", ")
+                    )
+                })?;
+            }
+            "v_fov" => {
+                camera.v_fov = tokens.parse("v_fov")?;
+                if !(camera.v_fov > 0.0 && camera.v_fov < 180.0) {
+                    bail!("v_fov has to be between 0 and 180 degrees");
+                }
+            }
+            "lookfrom" => camera.lookfrom = tokens.vec3("lookfrom")?,
+            "lookat" => camera.lookat = tokens.vec3("lookat")?,
+            "vup" => camera.vup = tokens.vec3("vup")?,
+            "defocus_angle" => camera.defocus_angle = tokens.non_negative("defocus_angle")?,
+            "focus_dist" => camera.focus_dist = tokens.positive_f64("focus_dist")?,
+            "shutter_open" => camera.shutter_open = tokens.parse("shutter_open")?,
+            "shutter_close" => camera.shutter_close = tokens.parse("shutter_close")?,
+            "threads" => camera.threads = tokens.parse("threads")?,
+            "seed" => camera.seed = Some(tokens.parse("seed")?),
+            other => bail!("unknown camera field '{}'", other),
+        }
+
+        Ok(())
+    }
+
+    fn texture(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let name = tokens.word("texture name")?;
+        if self.textures.contains_key(name) {
+            bail!("texture '{}' is already defined", name);
+        }
+
+        let texture: Arc<dyn Texture> = match tokens.word("texture type")? {
+            "solid" => Arc::new(SolidColor::new(tokens.color("color")?)),
+            "checker" => {
+                let scale = tokens.positive_f64("scale")?;
+                let even = self.texture_ref(tokens)?;
+                let odd = self.texture_ref(tokens)?;
+                Arc::new(CheckerTexture::new(scale, even, odd))
+            }
+            "image" => {
+                let path = self.base_dir.join(tokens.word("image path")?);
+                Arc::new(ImageTexture::load(&path.to_string_lossy())?)
+            }
+            "noise" => {
+                let scale = tokens.positive_f64("scale")?;
+                let seed = tokens.optional("seed")?.unwrap_or(0);
+                Arc::new(NoiseTexture::new(seed, scale))
+            }
+            "turbulence" => {
+                let scale = tokens.positive_f64("scale")?;
+                let octaves = tokens.positive("octaves")?;
+                let seed = tokens.optional("seed")?.unwrap_or(0);
+                Arc::new(TurbulenceTexture::new(seed, scale, octaves))
+            }
+            "marble" => {
+                let scale = tokens.positive_f64("scale")?;
+                let octaves = tokens.positive("octaves")?;
+                let seed = tokens.optional("seed")?.unwrap_or(0);
+                Arc::new(MarbleTexture::new(seed, scale, octaves))
+            }
+            other => bail!("unknown texture type '{}'", other),
+        };
+
+        self.textures.insert(name.to_string(), texture);
+        Ok(())
+    }
+
+    fn material(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let name = tokens.word("material name")?;
+        if self.materials.contains_key(name) {
+            bail!("material '{}' is already defined", name);
+        }
+
+        let material: Arc<dyn Material> = match tokens.word("material type")? {
+            "lambertian" => Arc::new(Lambertian::from_texture(self.albedo(tokens, "albedo")?)),
+            "metal" => {
+                let albedo = self.albedo(tokens, "albedo")?;
+                let fuzz = tokens.non_negative("fuzz")?;
+                Arc::new(Metal::from_texture(albedo, fuzz))
+            }
+            "dielectric" => Arc::new(Dielectric::new(tokens.positive_f64("refraction_index")?)),
+            "diffuse_light" => Arc::new(DiffuseLight::from_texture(self.albedo(tokens, "emit")?)),
+            other => bail!("unknown material type '{}'", other),
+        };
+
+        self.materials.insert(name.to_string(), material);
+        Ok(())
+    }
+
+    fn geometry(&self, kind: &str, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        match kind {
+            "sphere" => self.sphere(tokens),
+            "moving_sphere" => self.moving_sphere(tokens),
+            "quad" => self.quad(tokens),
+            "box" => self.make_box(tokens),
+            "triangle" => self.triangle(tokens),
+            "mesh" => self.mesh(tokens),
+            "medium" => self.medium(tokens),
+            other => bail!("unknown statement '{}'", other),
+        }
+    }
+
+    fn light(&mut self, tokens: &mut Tokens) -> Result<()> {
+        // Only shapes that know how to sample directions towards themselves
+        let light = match tokens.word("light type")? {
+            kind @ ("sphere" | "moving_sphere" | "quad" | "box" | "triangle") => {
+                self.geometry(kind, tokens)?
+            }
+            other => bail!("'{}' can not be used as a light", other),
+        };
+
+        self.world.add(light.clone());
+        self.lights.add(light);
+        Ok(())
+    }
+
+    fn object(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let name = tokens.word("object name")?;
+        if self.objects.contains_key(name) {
+            bail!("object '{}' is already defined", name);
+        }
+
+        let object = self.geometry(tokens.word("object type")?, tokens)?;
+        self.objects.insert(name.to_string(), object);
+        Ok(())
+    }
+
+    fn instance(&mut self, tokens: &mut Tokens) -> Result<()> {
+        let mut transform = Transform::new(self.object_ref(tokens)?);
+        while let Some(operation) = tokens.peek() {
+            tokens.word("transform")?;
+            transform = match operation {
+                "translate" => transform.translate(tokens.vec3("translation")?),
+                "rotate" => {
+                    let axis = tokens.word("rotation axis")?;
+                    let degrees = tokens.parse("rotation angle")?;
+                    match axis {
+                        "x" => transform.rotate_x(degrees),
+                        "y" => transform.rotate_y(degrees),
+                        "z" => transform.rotate_z(degrees),
+                        other => bail!("unknown rotation axis '{}', expected x, y or z", other),
+                    }
+                }
+                "scale" => {
+                    let factors = tokens.vec3("scale")?;
+                    transform.scale(factors)?
+                }
+                other => bail!("unknown transform '{}'", other),
+            };
+        }
+
+        self.world.add(Arc::new(transform));
+        Ok(())
+    }
+
+    fn sphere(&self, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        let center = tokens.vec3("center")?;
+        let radius = tokens.positive_f64("radius")?;
+        let mat = self.material_ref(tokens)?;
+
+        Ok(Arc::new(Sphere::new(center, radius, mat)))
+    }
+
+    fn moving_sphere(&self, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        let center1 = tokens.vec3("start center")?;
+        let center2 = tokens.vec3("end center")?;
+        let radius = tokens.positive_f64("radius")?;
+        let mat = self.material_ref(tokens)?;
+
+        Ok(Arc::new(Sphere::new_moving(center1, center2, radius, mat)))
+    }
+
+    fn quad(&self, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        let q = tokens.vec3("corner")?;
+        let u = tokens.vec3("edge u")?;
+        let v = tokens.vec3("edge v")?;
+        if Vec3::cross(&u, &v).near_zero() {
+            bail!("quad edges can not be parallel or zero");
+        }
+        let mat = self.material_ref(tokens)?;
+
+        Ok(Arc::new(Quad::new(q, u, v, mat)))
+    }
+
+    fn make_box(&self, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        let a = tokens.vec3("corner a")?;
+        let b = tokens.vec3("corner b")?;
+        let mat = self.material_ref(tokens)?;
+
+        Ok(Arc::new(make_box(&a, &b, mat)))
+    }
+
+    fn triangle(&self, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        let a = tokens.vec3("vertex a")?;
+        let b = tokens.vec3("vertex b")?;
+        let c = tokens.vec3("vertex c")?;
+        if Vec3::cross(&(b - a), &(c - a)).near_zero() {
+            bail!("triangle is degenerate");
+        }
+        let mat = self.material_ref(tokens)?;
+
+        Ok(Arc::new(Triangle::new(a, b, c, mat)))
+    }
+
+    fn mesh(&self, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        let path = self.base_dir.join(tokens.word("mesh path")?);
+        let mat = match tokens.peek() {
+            Some(_) => Some(self.material_ref(tokens)?),
+            None => None,
+        };
+
+        Ok(Arc::new(obj::load(&path, mat)?))
+    }
+
+    fn medium(&self, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        let boundary = self.object_ref(tokens)?;
+        let density = tokens.positive_f64("density")?;
+        let phase_function = Arc::new(Isotropic::from_texture(self.albedo(tokens, "albedo")?));
+        Ok(Arc::new(ConstantMedium::with_phase_function(
+            boundary,
+            density,
+            phase_function,
+        )))
+    }
+
+    fn bvh(&mut self, tokens: &mut Tokens) -> Result<()> {
+        self.bvh = match tokens.word("bvh split method")? {
+            "sah" => Some(SplitMethod::Sah),
+            "median" => Some(SplitMethod::Median),
+            "none" => None,
+            other => bail!("unknown bvh split method '{}'", other),
+        };
+
+        Ok(())
+    }
+
+    fn texture_ref(&self, tokens: &mut Tokens) -> Result<Arc<dyn Texture>> {
+        let name = tokens.word("texture")?;
+        self.textures
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("texture '{}' is not defined", name))
+    }
+
+    // A color written inline, or the name of a texture
+    fn albedo(&self, tokens: &mut Tokens, what: &str) -> Result<Arc<dyn Texture>> {
+        match tokens.peek() {
+            Some(token) if token.parse::<f64>().is_ok() => {
+                Ok(Arc::new(SolidColor::new(tokens.color(what)?)))
+            }
+            _ => self.texture_ref(tokens),
+        }
+    }
+
+    fn object_ref(&self, tokens: &mut Tokens) -> Result<Arc<dyn Hittable>> {
+        let name = tokens.word("object")?;
+        self.objects
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("object '{}' is not defined", name))
+    }
+
+    fn material_ref(&self, tokens: &mut Tokens) -> Result<Arc<dyn Material>> {
+        let name = tokens.word("material")?;
+        self.materials
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("material '{}' is not defined", name))
+    }
+
+    fn finish(self) -> Result<Scene> {
+        if self.camera.shutter_close < self.camera.shutter_open {
+            bail!("camera shutter_close can not come before shutter_open");
+        }
+
+        let world = self.world.tag_objects();
+        let world = match self.bvh {
+            Some(split) => HittableList::new(Arc::new(BvhNode::new(world, split))),
+            None => world,
+        };
+
+        Ok(Scene {
+            world,
+            lights: self.lights,
+            camera: self.camera,
+        })
+    }
+}
+
+// Whitespace separated arguments of a single statement
+struct Tokens<'a> {
+    inner: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(line: &'a str) -> Self {
+        Self {
+            inner: line.split_whitespace(),
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<&'a str> {
+        self.inner.next().ok_or_else(|| anyhow!("missing {}", what))
+    }
+
+    fn parse<T>(&mut self, what: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let token = self.word(what)?;
+        token
+            .parse()
+            .map_err(|err| anyhow!("invalid {} '{}': {}", what, token, err))
+    }
+
+    // Trailing argument that may be left out
+    fn optional<T>(&mut self, what: &str) -> Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.peek() {
+            Some(_) => Ok(Some(self.parse(what)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn positive(&mut self, what: &str) -> Result<u32> {
+        let value: u32 = self.parse(what)?;
+        if value == 0 {
+            bail!("{} has to be at least 1", what);
+        }
+        Ok(value)
+    }
+
+    fn positive_f64(&mut self, what: &str) -> Result<f64> {
+        let value: f64 = self.parse(what)?;
+        if !(value > 0.0 && value.is_finite()) {
+            bail!("{} has to be a positive number, got {}", what, value);
+        }
+        Ok(value)
+    }
+
+    fn non_negative(&mut self, what: &str) -> Result<f64> {
+        let value: f64 = self.parse(what)?;
+        if !(value >= 0.0 && value.is_finite()) {
+            bail!("{} can not be negative, got {}", what, value);
+        }
+        Ok(value)
+    }
+
+    // Accepts either a plain number or a fraction such as 16/9
+    fn ratio(&mut self, what: &str) -> Result<f64> {
+        let token = self.word(what)?;
+        parse_ratio(token).ok_or_else(|| {
+            anyhow!(
+                "{} has to be a positive number or fraction, got '{}'",
+                what,
+                token
+            )
+        })
+    }
+
+    fn vec3(&mut self, what: &str) -> Result<Vec3> {
+        Ok(Point3::new(
+            self.parse(what)?,
+            self.parse(what)?,
+            self.parse(what)?,
+        ))
+    }
+
+    fn color(&mut self, what: &str) -> Result<Color> {
+        let color = self.vec3(what)?;
+        if color.e.iter().any(|c| *c < 0.0) {
+            bail!("{} can not have negative components", what);
+        }
+        Ok(color)
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.inner.clone().next()
+    }
+
+    fn background(&mut self) -> Result<Background> {
+        match self.peek() {
+            Some("sky") => {
+                self.inner.next();
+                Ok(Background::Sky)
+            }
+            _ => Ok(Background::Solid(self.color("background")?)),
+        }
+    }
+
+    // Makes sure nothing is left over at the end of the statement
+    fn finish(&mut self) -> Result<()> {
+        match self.inner.next() {
+            Some(extra) => Err(anyhow!("unexpected argument '{}'", extra)),
+            None => Ok(()),
+        }
+    }
+}
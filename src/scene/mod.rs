@@ -0,0 +1,12 @@
+use crate::{camera::Camera, hittable::hittable_list::HittableList};
+
+pub mod builtin;
+pub mod loader;
+pub mod obj;
+
+// Everything needed to render an image: the objects and the camera looking at them
+pub struct Scene {
+    pub world: HittableList,
+    pub lights: HittableList, // Emitters that are sampled directly, they are in the world too
+    pub camera: Camera,
+}
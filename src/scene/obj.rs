@@ -0,0 +1,385 @@
+// Wavefront OBJ loader.
+//
+// Supports positions (v), normals (vn), texture coordinates (vt) and polygon faces (f) in any
+// of the v, v/vt, v//vn and v/vt/vn forms, including negative (relative) indices. Polygons are
+// triangulated as a fan, so they are expected to be convex. Materials come from the files named
+// by `mtllib` and are selected with `usemtl`, other statements (groups, smoothing...) are ignored.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use crate::{
+    hittable::{
+        material::{
+            Material, dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian,
+            metal::Metal,
+        },
+        mesh::{Face, Mesh, MeshBuffers},
+    },
+    vec3::{Color, Point3, Vec3},
+};
+
+// Loads a mesh, `material` replaces whatever the .mtl files define when given
+pub fn load(path: &Path, material: Option<Arc<dyn Material>>) -> Result<Mesh> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read OBJ file {}", path.display()))?;
+
+    let mut parser = ObjParser::new(path, material);
+    for (idx, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        parser
+            .statement(line)
+            .map_err(|err| anyhow!("line {}: {}", idx + 1, err))
+            .with_context(|| format!("Invalid OBJ file {}", path.display()))?;
+    }
+
+    if parser.faces.is_empty() {
+        bail!("OBJ file {} does not contain any faces", path.display());
+    }
+
+    Ok(Mesh::new(parser.buffers, parser.faces))
+}
+
+struct ObjParser {
+    dir: PathBuf, // .mtl files are looked up next to the .obj
+    buffers: MeshBuffers,
+    faces: Vec<Face>,
+    materials: HashMap<String, Arc<dyn Material>>,
+    current: Arc<dyn Material>,
+    forced: bool, // Material was given by the caller, ignore mtllib and usemtl
+}
+
+impl ObjParser {
+    fn new(path: &Path, material: Option<Arc<dyn Material>>) -> Self {
+        let forced = material.is_some();
+        let current =
+            material.unwrap_or_else(|| Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+
+        Self {
+            dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            buffers: MeshBuffers::default(),
+            faces: vec![],
+            materials: HashMap::new(),
+            current,
+            forced,
+        }
+    }
+
+    fn statement(&mut self, line: &str) -> Result<()> {
+        let mut tokens = line.split_whitespace();
+        let keyword = tokens.next().unwrap_or_default();
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = numbers(&args, "vertex position")?;
+                self.buffers.positions.push(Point3::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = numbers(&args, "vertex normal")?;
+                self.buffers
+                    .normals
+                    .push(Vec3::unit_vector(&Vec3::new(x, y, z)));
+            }
+            "vt" => {
+                // v is optional, and a third coordinate is allowed but not used
+                let u = number(args.first(), "texture coordinate")?;
+                let v = match args.get(1) {
+                    Some(v) => number(Some(v), "texture coordinate")?,
+                    None => 0.0,
+                };
+                self.buffers.uvs.push((u, v));
+            }
+            "f" => self.face(&args)?,
+            "mtllib" if !self.forced => {
+                for file in args {
+                    let path = self.dir.join(file);
+                    let materials = load_mtl(&path)?;
+                    self.materials.extend(materials);
+                }
+            }
+            "usemtl" if !self.forced => {
+                let name = args
+                    .first()
+                    .ok_or_else(|| anyhow!("missing material name"))?;
+                self.current = self
+                    .materials
+                    .get(*name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("material '{}' is not defined", name))?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn face(&mut self, args: &[&str]) -> Result<()> {
+        if args.len() < 3 {
+            bail!("face needs at least 3 vertices, got {}", args.len());
+        }
+
+        let mut positions = vec![];
+        let mut uvs = vec![];
+        let mut normals = vec![];
+        for vertex in args {
+            let mut parts = vertex.split('/');
+            let position = parts.next().unwrap_or_default();
+            positions.push(resolve(position, self.buffers.positions.len(), "vertex")?);
+
+            match parts.next() {
+                Some(uv) if !uv.is_empty() => {
+                    uvs.push(resolve(uv, self.buffers.uvs.len(), "texture coordinate")?)
+                }
+                _ => {}
+            }
+            if let Some(normal) = parts.next() {
+                normals.push(resolve(normal, self.buffers.normals.len(), "normal")?);
+            }
+        }
+
+        // Attributes are only used when every vertex of the face has them
+        let has_uvs = uvs.len() == positions.len();
+        let has_normals = normals.len() == positions.len();
+
+        // Fan triangulation around the first vertex
+        for k in 1..positions.len() - 1 {
+            let corners = [0, k, k + 1];
+            self.faces.push(Face {
+                positions: corners.map(|c| positions[c]),
+                normals: has_normals.then(|| corners.map(|c| normals[c])),
+                uvs: has_uvs.then(|| corners.map(|c| uvs[c])),
+                mat: self.current.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+// OBJ indices start at 1, negative ones count back from the last element defined so far
+fn resolve(token: &str, len: usize, what: &str) -> Result<usize> {
+    let index: i64 = token
+        .parse()
+        .map_err(|_| anyhow!("invalid {} index '{}'", what, token))?;
+
+    let resolved = match index {
+        i if i > 0 => i - 1,
+        i if i < 0 => len as i64 + i,
+        _ => bail!("{} index can not be 0", what),
+    };
+
+    if resolved < 0 || resolved >= len as i64 {
+        bail!("{} index {} is out of range, {} defined", what, index, len);
+    }
+    Ok(resolved as usize)
+}
+
+fn number(token: Option<&&str>, what: &str) -> Result<f64> {
+    let token = token.ok_or_else(|| anyhow!("missing {}", what))?;
+    token
+        .parse()
+        .map_err(|_| anyhow!("invalid {} '{}'", what, token))
+}
+
+fn numbers<const N: usize>(args: &[&str], what: &str) -> Result<[f64; N]> {
+    let mut values = [0.0; N];
+    for (idx, value) in values.iter_mut().enumerate() {
+        *value = number(args.get(idx), what)?;
+    }
+    Ok(values)
+}
+
+// Material statements of a .mtl file that we know how to map onto our materials
+#[derive(Default)]
+struct MtlEntry {
+    diffuse: Color,        // Kd
+    specular: Color,       // Ks
+    emission: Color,       // Ke
+    shininess: f64,        // Ns
+    refraction_index: f64, // Ni
+    dissolve: f64,         // d, 1 is opaque
+    illum: u32,
+}
+
+impl MtlEntry {
+    fn new() -> Self {
+        Self {
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            refraction_index: 1.5,
+            dissolve: 1.0,
+            illum: 2,
+            ..Default::default()
+        }
+    }
+
+    // Picks the closest of our materials, emission wins, then glass, then mirrors
+    fn build(&self) -> Arc<dyn Material> {
+        let max = |c: &Color| c.x().max(c.y()).max(c.z());
+
+        if max(&self.emission) > 0.0 {
+            Arc::new(DiffuseLight::new(self.emission))
+        } else if matches!(self.illum, 4 | 6 | 7 | 9) || self.dissolve < 1.0 {
+            Arc::new(Dielectric::new(self.refraction_index))
+        } else if matches!(self.illum, 3 | 5 | 8) && max(&self.specular) > 0.0 {
+            // Map the Phong exponent onto a fuzz radius, high exponents are sharp reflections
+            let fuzz = f64::sqrt(2.0 / (self.shininess.max(0.0) + 2.0));
+            Arc::new(Metal::new(self.specular, fuzz))
+        } else {
+            Arc::new(Lambertian::new(self.diffuse))
+        }
+    }
+}
+
+fn load_mtl(path: &Path) -> Result<HashMap<String, Arc<dyn Material>>> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Could not read MTL file {}", path.display()))?;
+
+    let mut parser = MtlParser::default();
+    for (idx, line) in source.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        parser
+            .statement(line)
+            .map_err(|err| anyhow!("line {}: {}", idx + 1, err))
+            .with_context(|| format!("Invalid MTL file {}", path.display()))?;
+    }
+
+    Ok(parser.finish())
+}
+
+#[derive(Default)]
+struct MtlParser {
+    materials: HashMap<String, Arc<dyn Material>>,
+    current: Option<(String, MtlEntry)>,
+}
+
+impl MtlParser {
+    fn statement(&mut self, line: &str) -> Result<()> {
+        let mut tokens = line.split_whitespace();
+        let keyword = tokens.next().unwrap_or_default();
+        let args: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            let name = args
+                .first()
+                .ok_or_else(|| anyhow!("missing material name"))?;
+            self.close_current();
+            self.current = Some((name.to_string(), MtlEntry::new()));
+            return Ok(());
+        }
+
+        let Some((_, entry)) = self.current.as_mut() else {
+            // Statements before the first newmtl have nothing to apply to
+            return Ok(());
+        };
+
+        match keyword {
+            "Kd" => entry.diffuse = color(&args, "Kd")?,
+            "Ks" => entry.specular = color(&args, "Ks")?,
+            "Ke" => entry.emission = color(&args, "Ke")?,
+            "Ns" => entry.shininess = number(args.first(), "Ns")?,
+            "Ni" => entry.refraction_index = number(args.first(), "Ni")?,
+            "d" => entry.dissolve = number(args.first(), "d")?,
+            "Tr" => entry.dissolve = 1.0 - number(args.first(), "Tr")?,
+            "illum" => entry.illum = number(args.first(), "illum")? as u32,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn close_current(&mut self) {
+        if let Some((name, entry)) = self.current.take() {
+            self.materials.insert(name, entry.build());
+        }
+    }
+
+    fn finish(mut self) -> HashMap<String, Arc<dyn Material>> {
+        self.close_current();
+        self.materials
+    }
+}
+
+fn color(args: &[&str], what: &str) -> Result<Color> {
+    let [r, g, b] = numbers(args, what)?;
+    if r < 0.0 || g < 0.0 || b < 0.0 {
+        bail!("{} can not have negative components", what);
+    }
+    Ok(Color::new(r, g, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ObjParser> {
+        let mut parser = ObjParser::new(Path::new("test.obj"), None);
+        for line in source.lines() {
+            parser.statement(line.trim())?;
+        }
+        Ok(parser)
+    }
+
+    #[test]
+    fn triangulates_faces_of_every_form() {
+        let parser = parse(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvt 1\nvn 0 0 2\n\
+             f 1 2 3 4\n\
+             f -4/1 -3/2 -2/1\n\
+             f 1//1 2//1 3//1\n\
+             f 1/1/1 2/2/1 3/1\n",
+        )
+        .unwrap();
+
+        let faces = &parser.faces;
+        assert_eq!(faces.len(), 5);
+        assert_eq!(faces[0].positions, [0, 1, 2]);
+        assert_eq!(faces[1].positions, [0, 2, 3]);
+        assert_eq!(faces[2].positions, [0, 1, 2]);
+        assert_eq!(faces[2].uvs, Some([0, 1, 0]));
+        assert_eq!(faces[2].normals, None);
+        assert_eq!(faces[3].normals, Some([0, 0, 0]));
+        assert_eq!(faces[3].uvs, None);
+
+        // A vertex without a normal drops them for the whole face
+        assert_eq!(faces[4].normals, None);
+        assert_eq!(parser.buffers.uvs[1], (1.0, 0.0));
+        assert_eq!(parser.buffers.normals[0].e, [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn rejects_bad_statements() {
+        let cases = [
+            (
+                "v 0 0 0\nf 1 2 3",
+                "vertex index 2 is out of range, 1 defined",
+            ),
+            ("v 0 0 0\nf 1 0 1", "vertex index can not be 0"),
+            (
+                "v 0 0 0\nf -2 1 1",
+                "vertex index -2 is out of range, 1 defined",
+            ),
+            ("v 0 0 0\nf 1 1", "face needs at least 3 vertices, got 2"),
+            ("v 0 zero 0", "invalid vertex position 'zero'"),
+            ("usemtl red", "material 'red' is not defined"),
+        ];
+        for (source, message) in cases {
+            let error = parse(source).err().map(|e| e.to_string());
+            assert_eq!(error.as_deref(), Some(message), "{:?}", source);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use crate::vec3::{Color, Point3};
+
+use super::{Texture, solid_color::SolidColor};
+
+// Spatial checker pattern, alternates between two textures in cubes of size `scale`
+pub struct CheckerTexture {
+    inv_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl CheckerTexture {
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> Self {
+        Self {
+            inv_scale: 1.0 / scale,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(scale: f64, c1: Color, c2: Color) -> Self {
+        Self::new(
+            scale,
+            Arc::new(SolidColor::new(c1)),
+            Arc::new(SolidColor::new(c2)),
+        )
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let x_integer = f64::floor(self.inv_scale * p.x()) as i64;
+        let y_integer = f64::floor(self.inv_scale * p.y()) as i64;
+        let z_integer = f64::floor(self.inv_scale * p.z()) as i64;
+
+        let is_even = (x_integer + y_integer + z_integer) % 2 == 0;
+
+        if is_even {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Result;
+
+use crate::{
+    image::Image,
+    utility::interval::Interval,
+    vec3::{Color, Point3},
+};
+
+use super::Texture;
+
+// Texture backed by an image, (0, 0) is the bottom left corner and (1, 1) the top right
+pub struct ImageTexture {
+    image: Image,
+}
+
+impl ImageTexture {
+    pub fn new(image: Image) -> Self {
+        Self { image }
+    }
+
+    pub fn load(image_path: &str) -> Result<Self> {
+        Ok(Self::new(Image::load(image_path)?))
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> Color {
+        // If we have no texture data, then return solid cyan as a debugging aid
+        if self.image.width() == 0 || self.image.height() == 0 {
+            return Color::new(0.0, 1.0, 1.0);
+        }
+
+        // Clamp input texture coordinates to [0,1] x [1,0]
+        let u = Interval::new(0.0, 1.0).clamp(u);
+        let v = 1.0 - Interval::new(0.0, 1.0).clamp(v); // Flip V to image coordinates
+
+        let i = ((u * self.image.width() as f64) as u32).min(self.image.width() - 1);
+        let j = ((v * self.image.height() as f64) as u32).min(self.image.height() - 1);
+
+        self.image.pixel(i, j)
+    }
+}
@@ -0,0 +1,11 @@
+use crate::vec3::{Color, Point3};
+
+pub mod checker;
+pub mod image_texture;
+pub mod noise;
+pub mod solid_color;
+
+// Color lookup at a surface point, from its (u, v) coordinates, its position, or both
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
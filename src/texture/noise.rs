@@ -0,0 +1,74 @@
+use crate::{
+    utility::perlin::Perlin,
+    vec3::{Color, Point3},
+};
+
+use super::Texture;
+
+// Plain Perlin noise, remapped from [-1, 1] to [0, 1]
+pub struct NoiseTexture {
+    noise: Perlin,
+    scale: f64,
+}
+
+impl NoiseTexture {
+    pub fn new(seed: u64, scale: f64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        Color::new(1.0, 1.0, 1.0) * 0.5 * (1.0 + self.noise.noise(&(self.scale * *p)))
+    }
+}
+
+// Several octaves of noise summed together
+pub struct TurbulenceTexture {
+    noise: Perlin,
+    scale: f64,
+    octaves: u32,
+}
+
+impl TurbulenceTexture {
+    pub fn new(seed: u64, scale: f64, octaves: u32) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            octaves,
+        }
+    }
+}
+
+impl Texture for TurbulenceTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        Color::new(1.0, 1.0, 1.0) * self.noise.turb(&(self.scale * *p), self.octaves)
+    }
+}
+
+// Sine stripes along z with their phase disturbed by turbulence
+pub struct MarbleTexture {
+    noise: Perlin,
+    scale: f64,
+    octaves: u32,
+}
+
+impl MarbleTexture {
+    pub fn new(seed: u64, scale: f64, octaves: u32) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            octaves,
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let phase = self.scale * p.z() + 10.0 * self.noise.turb(p, self.octaves);
+        Color::new(1.0, 1.0, 1.0) * 0.5 * (1.0 + f64::sin(phase))
+    }
+}
@@ -1,5 +1,4 @@
-use std::f64::INFINITY;
-
+#[derive(Clone, Copy, Debug)]
 pub struct Interval {
     pub min: f64,
     pub max: f64,
@@ -10,6 +9,14 @@ impl Interval {
         Self { min, max }
     }
 
+    // Tightest interval that contains both of the given ones
+    pub fn enclosing(a: &Interval, b: &Interval) -> Self {
+        Self {
+            min: f64::min(a.min, b.min),
+            max: f64::max(a.max, b.max),
+        }
+    }
+
     pub fn size(&self) -> f64 {
         self.max - self.min
     }
@@ -25,23 +32,28 @@ impl Interval {
     pub fn clamp(&self, x: f64) -> f64 {
         f64::clamp(x, self.min, self.max)
     }
+
+    pub fn expand(&self, delta: f64) -> Interval {
+        let padding = delta / 2.0;
+        Interval::new(self.min - padding, self.max + padding)
+    }
 }
 
 pub const EMPTY: Interval = Interval {
-    min: INFINITY,
-    max: -INFINITY,
+    min: f64::INFINITY,
+    max: -f64::INFINITY,
 };
 pub const UNIVERSE: Interval = Interval {
-    min: -INFINITY,
-    max: INFINITY,
+    min: -f64::INFINITY,
+    max: f64::INFINITY,
 };
 
 impl Default for Interval {
     fn default() -> Self {
         // Default is empty
         Self {
-            min: INFINITY,
-            max: -INFINITY,
+            min: f64::INFINITY,
+            max: -f64::INFINITY,
         }
     }
 }
@@ -1,19 +1,57 @@
-use std::f64::consts::PI;
+use std::{cell::RefCell, f64::consts::PI};
 
-use rand::Rng;
+use pcg::Pcg32;
 
 pub mod interval;
+pub mod pcg;
+pub mod perlin;
+
+thread_local! {
+    // Every thread owns its generator, so sampling never contends on a lock. Until it is
+    // seeded it starts from OS entropy
+    static RNG: RefCell<Pcg32> = RefCell::new(Pcg32::new(rand::random(), rand::random()));
+}
+
+// Restart the current thread's generator from a fixed seed, making what follows reproducible
+pub fn seed_rng(seed: u64) {
+    seed_rng_stream(seed, 0);
+}
+
+// Same as `seed_rng` on one of many independent streams, the renderer gives every pixel
+// sample its own stream so the result does not depend on the order samples are taken in
+pub fn seed_rng_stream(seed: u64, stream: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = Pcg32::new(seed, stream));
+}
 
 // Generate value between 0.0 and 1.0
 pub fn random_double() -> f64 {
-    let mut rng = rand::rng();
-    rng.random()
+    RNG.with(|rng| rng.borrow_mut().next_f64())
 }
 
 pub fn random_double_clamp(min: f64, max: f64) -> f64 {
     min + (max - min) * random_double()
 }
 
+// Mixes a list of values into one well scrambled 64 bit hash (splitmix64 finalizer)
+pub fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x243F_6A88_85A3_08D3, |h, value| {
+        let mut z = (h ^ value).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    })
+}
+
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.0
 }
+
+// Parses either a plain number or a fraction such as 16/9
+pub fn parse_ratio(text: &str) -> Option<f64> {
+    let value = match text.split_once('/') {
+        Some((num, den)) => num.parse::<f64>().ok()? / den.parse::<f64>().ok()?,
+        None => text.parse().ok()?,
+    };
+
+    (value > 0.0 && value.is_finite()).then_some(value)
+}
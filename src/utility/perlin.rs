@@ -0,0 +1,120 @@
+use crate::vec3::{Point3, Vec3};
+
+use super::pcg::Pcg32;
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise on a lattice of random unit vectors. The same seed always builds the same
+// lattice, so noise based textures look identical from one run to the next
+pub struct Perlin {
+    randvec: [Vec3; POINT_COUNT],
+    perm_x: [usize; POINT_COUNT],
+    perm_y: [usize; POINT_COUNT],
+    perm_z: [usize; POINT_COUNT],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Pcg32::new(seed, 0);
+
+        let mut randvec = [Vec3::default(); POINT_COUNT];
+        for vec in randvec.iter_mut() {
+            *vec = loop {
+                let p = Vec3::new(
+                    rng.range_f64(-1.0, 1.0),
+                    rng.range_f64(-1.0, 1.0),
+                    rng.range_f64(-1.0, 1.0),
+                );
+                let lensq = p.length_squared();
+                if 1e-160 < lensq && lensq <= 1.0 {
+                    break p / lensq.sqrt();
+                }
+            };
+        }
+
+        Self {
+            randvec,
+            perm_x: Self::generate_perm(&mut rng),
+            perm_y: Self::generate_perm(&mut rng),
+            perm_z: Self::generate_perm(&mut rng),
+        }
+    }
+
+    // Smooth noise in [-1, 1]
+    pub fn noise(&self, p: &Point3) -> f64 {
+        let u = p.x() - p.x().floor();
+        let v = p.y() - p.y().floor();
+        let w = p.z() - p.z().floor();
+
+        let i = p.x().floor() as i64;
+        let j = p.y().floor() as i64;
+        let k = p.z().floor() as i64;
+
+        // Gradients at the eight corners of the lattice cell around p
+        let mut c = [[[Vec3::default(); 2]; 2]; 2];
+        for (di, plane) in c.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    *corner = self.randvec[self.perm_x[((i + di as i64) & 255) as usize]
+                        ^ self.perm_y[((j + dj as i64) & 255) as usize]
+                        ^ self.perm_z[((k + dk as i64) & 255) as usize]];
+                }
+            }
+        }
+
+        Self::perlin_interp(&c, u, v, w)
+    }
+
+    // Sum of `octaves` noise layers, each at double the frequency and half the weight
+    pub fn turb(&self, p: &Point3, octaves: u32) -> f64 {
+        let mut accum = 0.0;
+        let mut temp_p = *p;
+        let mut weight = 1.0;
+
+        for _ in 0..octaves {
+            accum += weight * self.noise(&temp_p);
+            weight *= 0.5;
+            temp_p = temp_p * 2.0;
+        }
+
+        f64::abs(accum)
+    }
+
+    fn generate_perm(rng: &mut Pcg32) -> [usize; POINT_COUNT] {
+        let mut p = [0; POINT_COUNT];
+        for (idx, value) in p.iter_mut().enumerate() {
+            *value = idx;
+        }
+
+        // Fisher-Yates shuffle
+        for i in (1..POINT_COUNT).rev() {
+            let target = rng.below(i as u32 + 1) as usize;
+            p.swap(i, target);
+        }
+
+        p
+    }
+
+    // Trilinear interpolation of the corner gradients, with Hermite smoothing of the weights
+    fn perlin_interp(c: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+        let uu = u * u * (3.0 - 2.0 * u);
+        let vv = v * v * (3.0 - 2.0 * v);
+        let ww = w * w * (3.0 - 2.0 * w);
+        let mut accum = 0.0;
+
+        for (i, plane) in c.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, corner) in row.iter().enumerate() {
+                    let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                    let weight_v = Vec3::new(u - fi, v - fj, w - fk);
+                    accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * Vec3::dot(corner, &weight_v);
+                }
+            }
+        }
+
+        accum
+    }
+}
@@ -0,0 +1,136 @@
+use std::ops::Mul;
+
+use crate::utility::degrees_to_radians;
+
+use super::Vec3;
+
+// Row-major 3x3 matrix, the linear part of an affine transform
+#[derive(Clone, Copy, Debug)]
+pub struct Mat3 {
+    pub m: [[f64; 3]; 3],
+}
+
+impl Default for Mat3 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Mat3 {
+    pub fn identity() -> Self {
+        Self::from_diagonal(&Vec3::new(1.0, 1.0, 1.0))
+    }
+
+    pub fn from_diagonal(d: &Vec3) -> Self {
+        Self {
+            m: [[d[0], 0.0, 0.0], [0.0, d[1], 0.0], [0.0, 0.0, d[2]]],
+        }
+    }
+
+    // Counter-clockwise rotation around a unit axis (Rodrigues' formula)
+    pub fn rotation(axis: &Vec3, degrees: f64) -> Self {
+        let a = Vec3::unit_vector(axis);
+        let theta = degrees_to_radians(degrees);
+        let (sin, cos) = theta.sin_cos();
+        let t = 1.0 - cos;
+
+        Self {
+            m: [
+                [
+                    t * a.x() * a.x() + cos,
+                    t * a.x() * a.y() - sin * a.z(),
+                    t * a.x() * a.z() + sin * a.y(),
+                ],
+                [
+                    t * a.x() * a.y() + sin * a.z(),
+                    t * a.y() * a.y() + cos,
+                    t * a.y() * a.z() - sin * a.x(),
+                ],
+                [
+                    t * a.x() * a.z() - sin * a.y(),
+                    t * a.y() * a.z() + sin * a.x(),
+                    t * a.z() * a.z() + cos,
+                ],
+            ],
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let m = &self.m;
+        Self {
+            m: [
+                [m[0][0], m[1][0], m[2][0]],
+                [m[0][1], m[1][1], m[2][1]],
+                [m[0][2], m[1][2], m[2][2]],
+            ],
+        }
+    }
+
+    pub fn determinant(&self) -> f64 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    // Inverse from the adjugate, None if the matrix is singular. The determinant is compared
+    // to the product of the row lengths, its largest possible size, so a uniformly tiny but
+    // well shaped matrix still counts as invertible
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        let bound: f64 = self
+            .m
+            .iter()
+            .map(|row| Vec3::new(row[0], row[1], row[2]).length())
+            .product();
+        if det.abs() <= 1e-12 * bound || !det.recip().is_finite() {
+            return None;
+        }
+
+        let m = &self.m;
+        let inv_det = 1.0 / det;
+        Some(Self {
+            m: [
+                [
+                    (m[1][1] * m[2][2] - m[1][2] * m[2][1]) * inv_det,
+                    (m[0][2] * m[2][1] - m[0][1] * m[2][2]) * inv_det,
+                    (m[0][1] * m[1][2] - m[0][2] * m[1][1]) * inv_det,
+                ],
+                [
+                    (m[1][2] * m[2][0] - m[1][0] * m[2][2]) * inv_det,
+                    (m[0][0] * m[2][2] - m[0][2] * m[2][0]) * inv_det,
+                    (m[0][2] * m[1][0] - m[0][0] * m[1][2]) * inv_det,
+                ],
+                [
+                    (m[1][0] * m[2][1] - m[1][1] * m[2][0]) * inv_det,
+                    (m[0][1] * m[2][0] - m[0][0] * m[2][1]) * inv_det,
+                    (m[0][0] * m[1][1] - m[0][1] * m[1][0]) * inv_det,
+                ],
+            ],
+        })
+    }
+}
+
+impl Mul<Mat3> for Mat3 {
+    type Output = Mat3;
+
+    fn mul(self, rhs: Mat3) -> Self::Output {
+        let mut res = [[0.0; 3]; 3];
+        for (i, row) in res.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+
+        Mat3 { m: res }
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Vec3) -> Self::Output {
+        let row = |i: usize| self.m[i][0] * rhs[0] + self.m[i][1] * rhs[1] + self.m[i][2] * rhs[2];
+        Vec3::new(row(0), row(1), row(2))
+    }
+}
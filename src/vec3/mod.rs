@@ -1,12 +1,13 @@
 use std::{
+    f64::consts::PI,
     fmt::Display,
     ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub},
 };
 
-use crate::{
-    camera,
-    utility::{random_double, random_double_clamp},
-};
+use crate::utility::{random_double, random_double_clamp};
+
+pub mod mat3;
+pub mod onb;
 
 #[derive(Default, Clone, Copy, Debug)]
 pub struct Vec3 {
@@ -59,14 +60,20 @@ impl Vec3 {
     pub fn unit_vector(v: &Vec3) -> Vec3 {
         *v / v.length()
     }
-    
-    pub fn random_in_unit_disc() -> Vec3 {
-        loop {
-            let p = Vec3::new(random_double_clamp(-1.0, 1.0), random_double_clamp(-1.0, 1.0), 0.0);
-            if p.length_squared() < 1.0 {
-                return p;
-            }
-        }
+
+    // Point in the unit disc at z = 0, uniform by area, from a sample in [0, 1)^2
+    pub fn sample_unit_disc(u: (f64, f64)) -> Vec3 {
+        let r = f64::sqrt(u.0);
+        let phi = 2.0 * PI * u.1;
+        Vec3::new(r * f64::cos(phi), r * f64::sin(phi), 0.0)
+    }
+
+    // Uniform direction over the whole sphere
+    pub fn sample_unit_sphere(u: (f64, f64)) -> Vec3 {
+        let z = 1.0 - 2.0 * u.0;
+        let r = f64::sqrt(f64::max(0.0, 1.0 - z * z));
+        let phi = 2.0 * PI * u.1;
+        Vec3::new(r * f64::cos(phi), r * f64::sin(phi), z)
     }
 
     pub fn near_zero(&self) -> bool {
@@ -75,6 +82,15 @@ impl Vec3 {
         (f64::abs(self.e[0]) < s) && (f64::abs(self.e[1]) < s) && (f64::abs(self.e[2]) < s)
     }
 
+    // Relative luminance when used as a linear color (Rec. 709 weights)
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.e[0] + 0.7152 * self.e[1] + 0.0722 * self.e[2]
+    }
+
+    pub fn max_component(&self) -> f64 {
+        f64::max(self.e[0], f64::max(self.e[1], self.e[2]))
+    }
+
     pub fn random_unit_vector() -> Vec3 {
         loop {
             let p = Vec3::random_clamp(-1.0, 1.0);
@@ -92,7 +108,7 @@ impl Vec3 {
         let on_unit_hemisphere = Vec3::random_unit_vector();
 
         // If positive that means out vector has "same" direction as normal vector
-        if Vec3::dot(&on_unit_hemisphere, &normal) > 0.0 {
+        if Vec3::dot(&on_unit_hemisphere, normal) > 0.0 {
             on_unit_hemisphere
         } else {
             -on_unit_hemisphere
@@ -100,16 +116,41 @@ impl Vec3 {
     }
 
     pub fn reflect(v: &Vec3, n: &Vec3) -> Vec3 {
-        *v - 2.0 * Self::dot(&v, n) * *n
+        *v - 2.0 * Self::dot(v, n) * *n
     }
 
     pub fn refract(uv: &Vec3, n: &Vec3, etai_over_etat: f64) -> Vec3 {
-        let cos_theta = f64::min(Vec3::dot(&-(*uv), &n), 1.0);
-        let r_out_perp = etai_over_etat * (uv.clone() + cos_theta * *n);
+        let cos_theta = f64::min(Vec3::dot(&-(*uv), n), 1.0);
+        let r_out_perp = etai_over_etat * (*uv + cos_theta * *n);
         let r_out_parallel = -((1.0 - r_out_perp.length_squared()).abs().sqrt()) * *n;
         r_out_perp + r_out_parallel
     }
 
+    // Direction on the hemisphere around +z with density cos(theta) / pi
+    pub fn sample_cosine_direction(u: (f64, f64)) -> Vec3 {
+        let (r1, r2) = u;
+
+        let phi = 2.0 * PI * r1;
+        let x = f64::cos(phi) * f64::sqrt(r2);
+        let y = f64::sin(phi) * f64::sqrt(r2);
+        let z = f64::sqrt(1.0 - r2);
+
+        Vec3::new(x, y, z)
+    }
+
+    // Direction towards a sphere of the given radius, seen from the given squared distance
+    // to its center, uniform over the cone of directions that hit it, around +z
+    pub fn sample_to_sphere(radius: f64, distance_squared: f64, u: (f64, f64)) -> Vec3 {
+        let (r1, r2) = u;
+        let z = 1.0 + r2 * (f64::sqrt(1.0 - radius * radius / distance_squared) - 1.0);
+
+        let phi = 2.0 * PI * r1;
+        let x = f64::cos(phi) * f64::sqrt(1.0 - z * z);
+        let y = f64::sin(phi) * f64::sqrt(1.0 - z * z);
+
+        Vec3::new(x, y, z)
+    }
+
     pub fn random() -> Vec3 {
         Vec3::new(random_double(), random_double(), random_double())
     }
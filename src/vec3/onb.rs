@@ -0,0 +1,33 @@
+use super::Vec3;
+
+// Orthonormal basis with w along a given direction, used to orient sampled directions
+#[derive(Clone, Copy, Debug)]
+pub struct Onb {
+    axis: [Vec3; 3],
+}
+
+impl Onb {
+    pub fn new(n: &Vec3) -> Self {
+        let w = Vec3::unit_vector(n);
+
+        // Any vector that is not parallel to w will do to build the other two axes
+        let a = if f64::abs(w.x()) > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = Vec3::unit_vector(&Vec3::cross(&w, &a));
+        let u = Vec3::cross(&w, &v);
+
+        Self { axis: [u, v, w] }
+    }
+
+    pub fn w(&self) -> Vec3 {
+        self.axis[2]
+    }
+
+    // Maps coordinates given in this basis to world space
+    pub fn transform(&self, v: &Vec3) -> Vec3 {
+        (v[0] * self.axis[0]) + (v[1] * self.axis[1]) + (v[2] * self.axis[2])
+    }
+}